@@ -0,0 +1,296 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use bevy::prelude::*;
+use bevy::input::InputSystem;
+use serde::{Deserialize, Serialize};
+
+/// Everything the player can do, whatever the device.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub enum Action {
+    MoveX,
+    MoveY,
+    Fire,
+    NextWeapon,
+    Pause,
+    ToggleDebugCamera,
+    ToggleColliders,
+    ToggleInspector,
+    ToggleConsole,
+}
+
+impl Action {
+    pub const ALL: [Action; 9] = [Action::MoveX, Action::MoveY, Action::Fire, Action::NextWeapon, Action::Pause,
+                                  Action::ToggleDebugCamera, Action::ToggleColliders, Action::ToggleInspector,
+                                  Action::ToggleConsole];
+
+    /// Axis actions go from -1 to 1, all others are buttons from 0 to 1.
+    pub fn is_axis(self) -> bool {
+        matches!(self, Action::MoveX | Action::MoveY)
+    }
+}
+
+/// One way to trigger an action. The pairs are the negative and positive side of an axis.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub enum Binding {
+    Key(KeyCode),
+    Keys(KeyCode, KeyCode),
+    GamepadButton(GamepadButtonType),
+    GamepadButtons(GamepadButtonType, GamepadButtonType),
+    GamepadAxis(GamepadAxisType),
+}
+
+impl Binding {
+    pub fn is_gamepad(&self) -> bool {
+        !matches!(self, Binding::Key(_) | Binding::Keys(_, _))
+    }
+
+    fn value(&self, devices: &Devices) -> f32 {
+        let key = |key| devices.keys.pressed(key) as i32 as f32;
+        let button = |button| devices.gamepads.iter()
+            .any(|gamepad| devices.buttons.pressed(GamepadButton::new(gamepad, button))) as i32 as f32;
+        match *self {
+            Binding::Key(k) => key(k),
+            Binding::Keys(negative, positive) => key(positive) - key(negative),
+            Binding::GamepadButton(b) => button(b),
+            Binding::GamepadButtons(negative, positive) => button(positive) - button(negative),
+            Binding::GamepadAxis(axis) => devices.gamepads.iter()
+                .filter_map(|gamepad| devices.axes.get(GamepadAxis::new(gamepad, axis)))
+                .fold(0.0, strongest),
+        }
+    }
+}
+
+impl fmt::Display for Binding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Binding::Key(key) => write!(f, "{key:?}"),
+            Binding::Keys(negative, positive) => write!(f, "{negative:?} / {positive:?}"),
+            Binding::GamepadButton(button) => write!(f, "Pad {button:?}"),
+            Binding::GamepadButtons(negative, positive) => write!(f, "Pad {negative:?} / {positive:?}"),
+            Binding::GamepadAxis(axis) => write!(f, "Pad {axis:?}"),
+        }
+    }
+}
+
+fn strongest(a: f32, b: f32) -> f32 {
+    if b.abs() > a.abs() { b } else { a }
+}
+
+/// Which bindings trigger which action, kept in the user's config directory.
+#[derive(Resource, Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct InputBindings {
+    pub actions: BTreeMap<Action, Vec<Binding>>,
+}
+
+impl Default for InputBindings {
+    fn default() -> Self {
+        use GamepadAxisType::*;
+        use GamepadButtonType::*;
+        InputBindings {
+            actions: BTreeMap::from([
+                (Action::MoveX, vec![Binding::Keys(KeyCode::ArrowLeft, KeyCode::ArrowRight),
+                                     Binding::GamepadAxis(LeftStickX),
+                                     Binding::GamepadButtons(DPadLeft, DPadRight)]),
+                (Action::MoveY, vec![Binding::Keys(KeyCode::ArrowDown, KeyCode::ArrowUp),
+                                     Binding::GamepadAxis(LeftStickY),
+                                     Binding::GamepadButtons(DPadDown, DPadUp)]),
+                (Action::Fire, vec![Binding::Key(KeyCode::Space),
+                                    Binding::GamepadButton(South),
+                                    Binding::GamepadButton(RightTrigger2)]),
+                (Action::NextWeapon, vec![Binding::Key(KeyCode::KeyW),
+                                          Binding::GamepadButton(RightTrigger)]),
+                (Action::Pause, vec![Binding::Key(KeyCode::Escape),
+                                     Binding::GamepadButton(Start)]),
+                (Action::ToggleDebugCamera, vec![Binding::Key(KeyCode::KeyO)]),
+                (Action::ToggleColliders, vec![Binding::Key(KeyCode::KeyP)]),
+                (Action::ToggleInspector, vec![Binding::Key(KeyCode::KeyD)]),
+                (Action::ToggleConsole, vec![Binding::Key(KeyCode::Backquote)]),
+            ]),
+        }
+    }
+}
+
+impl InputBindings {
+    /// `<config dir>/planet-rust/bindings.ron`, if the platform has a config directory.
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("planet-rust").join("bindings.ron"))
+    }
+
+    /// Actions missing from the file keep their default bindings.
+    pub fn load(path: &Path) -> io::Result<InputBindings> {
+        let text = fs::read_to_string(path)?;
+        let mut bindings: InputBindings = ron::from_str(&text)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        for (action, defaults) in InputBindings::default().actions {
+            bindings.actions.entry(action).or_insert(defaults);
+        }
+        Ok(bindings)
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let text = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        fs::write(path, text)
+    }
+
+    /// The bindings from the config file, or the defaults if there is none.
+    pub fn load_or_default() -> InputBindings {
+        let Some(path) = InputBindings::default_path() else {
+            return InputBindings::default();
+        };
+        match InputBindings::load(&path) {
+            Ok(bindings) => bindings,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => InputBindings::default(),
+            Err(e) => {
+                warn!("cannot read key bindings from {}: {e}", path.display());
+                InputBindings::default()
+            }
+        }
+    }
+
+    pub fn bindings(&self, action: Action) -> &[Binding] {
+        self.actions.get(&action).map_or(&[], |bindings| bindings.as_slice())
+    }
+
+    /// Replaces the bindings of the action on the same device (keyboard or gamepad).
+    pub fn rebind(&mut self, action: Action, binding: Binding) {
+        let bindings = self.actions.entry(action).or_default();
+        bindings.retain(|b| b.is_gamepad() != binding.is_gamepad());
+        bindings.push(binding);
+    }
+}
+
+/// The value of every action this frame, read by the gameplay instead of the devices.
+#[derive(Resource, Default)]
+pub struct ActionState {
+    values: BTreeMap<Action, f32>,
+    previous: BTreeMap<Action, f32>,
+}
+
+impl ActionState {
+    pub fn value(&self, action: Action) -> f32 {
+        self.values.get(&action).copied().unwrap_or(0.0)
+    }
+
+    pub fn pressed(&self, action: Action) -> bool {
+        self.value(action).abs() >= 0.5
+    }
+
+    pub fn just_pressed(&self, action: Action) -> bool {
+        self.pressed(action) && self.previous.get(&action).is_none_or(|value| value.abs() < 0.5)
+    }
+}
+
+/// Waits for the next key, button or stick to bind to an action.
+/// Axis actions take a stick, or two keys/buttons: first the negative side, then the positive one.
+#[derive(Resource, Default)]
+pub struct Rebinding {
+    pub action: Option<Action>,
+    first: Option<Binding>,
+}
+
+impl Rebinding {
+    pub fn start(&mut self, action: Action) {
+        self.action = Some(action);
+        self.first = None;
+    }
+
+    pub fn cancel(&mut self) {
+        self.action = None;
+        self.first = None;
+    }
+
+    pub fn waiting_for_positive(&self) -> bool {
+        self.first.is_some()
+    }
+}
+
+/// Turns keyboard and gamepads into [`ActionState`] before `Update`.
+pub struct ActionsPlugin;
+
+/// Where [`ActionState`] is updated in `PreUpdate`, input that should not reach
+/// the game is taken away before it.
+#[derive(SystemSet, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct ActionSystem;
+
+impl Plugin for ActionsPlugin {
+    fn build(&self, app: &mut App){
+        app
+            .init_resource::<InputBindings>()
+            .init_resource::<ActionState>()
+            .init_resource::<Rebinding>()
+            .add_systems(PreUpdate, (update_action_state, capture_binding).chain().in_set(ActionSystem).after(InputSystem));
+    }
+}
+
+#[derive(bevy::ecs::system::SystemParam)]
+struct Devices<'w> {
+    keys: Res<'w, ButtonInput<KeyCode>>,
+    gamepads: Res<'w, Gamepads>,
+    buttons: Res<'w, ButtonInput<GamepadButton>>,
+    axes: Res<'w, Axis<GamepadAxis>>,
+}
+
+const STICK_THRESHOLD: f32 = 0.7;
+
+fn update_action_state(
+    devices: Devices,
+    bindings: Res<InputBindings>,
+    rebinding: Res<Rebinding>,
+    mut action_state: ResMut<ActionState>
+){
+    let action_state = &mut *action_state;
+    action_state.previous = action_state.values.clone();
+    for action in Action::ALL {
+        // the input that is being bound must not trigger anything
+        let value = if rebinding.action.is_some() {
+            0.0
+        } else {
+            bindings.bindings(action).iter()
+                .map(|binding| binding.value(&devices))
+                .fold(0.0, strongest)
+        };
+        let value = if action.is_axis() { value.clamp(-1.0, 1.0) } else { value.clamp(0.0, 1.0) };
+        action_state.values.insert(action, value);
+    }
+}
+
+fn capture_binding(
+    devices: Devices,
+    mut rebinding: ResMut<Rebinding>,
+    mut bindings: ResMut<InputBindings>
+){
+    let Some(action) = rebinding.action else {
+        return;
+    };
+    let key = devices.keys.get_just_pressed().next().copied();
+    let button = devices.buttons.get_just_pressed().next().map(|button| button.button_type);
+    let stick = devices.axes.devices()
+        .find(|axis| devices.axes.get(**axis).is_some_and(|value| value.abs() > STICK_THRESHOLD))
+        .map(|axis| axis.axis_type);
+
+    let binding = match (action.is_axis(), rebinding.first, key, button, stick) {
+        (false, _, Some(key), _, _) => Binding::Key(key),
+        (false, _, _, Some(button), _) => Binding::GamepadButton(button),
+        (true, Some(Binding::Key(negative)), Some(key), _, _) => Binding::Keys(negative, key),
+        (true, Some(Binding::GamepadButton(negative)), _, Some(button), _) => Binding::GamepadButtons(negative, button),
+        (true, None, Some(key), _, _) => {
+            rebinding.first = Some(Binding::Key(key));
+            return;
+        }
+        (true, None, _, Some(button), _) => {
+            rebinding.first = Some(Binding::GamepadButton(button));
+            return;
+        }
+        (true, None, _, _, Some(stick)) => Binding::GamepadAxis(stick),
+        _ => return,
+    };
+    bindings.rebind(action, binding);
+    rebinding.cancel();
+}
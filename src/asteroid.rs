@@ -0,0 +1,89 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use rand::Rng;
+use crate::components::{Asteroid, Damage, Despawnable, Health, Opponent};
+use crate::damage::RAM_DAMAGE;
+use crate::events::AsteroidDestroyedEvent;
+use crate::game_state::InGame;
+use crate::resources::GameAssets;
+use crate::rng::GameRng;
+
+/// How many smaller asteroids a big one breaks into.
+const SPLIT_PIECES: usize = 3;
+/// Sideways speed of the pieces, they fly apart from where the asteroid was.
+const SPLIT_SPEED: f32 = 20.0;
+/// Fastest tumbling, in radians per second around each axis.
+const MAX_SPIN: f32 = 1.5;
+const RESTITUTION: f32 = 0.8;
+
+/// The body is not scaled, so the collider and the mass come from the asteroid's size
+/// while the rock model underneath is scaled to match.
+pub fn spawn_asteroid(
+    commands: &mut Commands,
+    game_assets: &GameAssets,
+    asteroid: Asteroid,
+    translation: Vec3,
+    velocity: Velocity,
+) {
+    commands.spawn(SpatialBundle::from_transform(Transform::from_translation(translation)))
+        .with_children(|parent| {
+            parent.spawn(SceneBundle {
+                scene: game_assets.opponent_2_scene.clone(),
+                transform: Transform::from_scale(Vec3::splat(asteroid.scale)),
+                ..default()
+            });
+        })
+        .insert(RigidBody::Dynamic)
+        .insert(velocity)
+        .insert(Collider::ball(asteroid.radius()))
+        .insert(ColliderMassProperties::Mass(asteroid.mass()))
+        .insert(Restitution::coefficient(RESTITUTION))
+        .insert(ExternalImpulse::default())
+        .insert(ActiveEvents::COLLISION_EVENTS)
+        .insert(GravityScale(0.0))
+        .insert(Despawnable {
+            min: -1000.0,
+            max: 0.0
+        })
+        .insert(Name::new("Opponent"))
+        .insert(Opponent {
+            points: asteroid.score(),
+        })
+        .insert(Health::new(asteroid.health()))
+        .insert(Damage {
+            amount: RAM_DAMAGE,
+        })
+        .insert(asteroid)
+        .insert(StateScoped(InGame));
+}
+
+/// Breaks destroyed asteroids into smaller pieces flying apart.
+pub fn split_asteroids(
+    mut commands: Commands,
+    mut event_destroyed: EventReader<AsteroidDestroyedEvent>,
+    game_assets: Res<GameAssets>,
+    mut game_rng: ResMut<GameRng>,
+){
+    let rng = &mut game_rng.asteroids;
+    for event in event_destroyed.read() {
+        let Some(piece) = event.asteroid.piece() else {
+            continue;
+        };
+        let start = rng.gen_range(0.0..std::f32::consts::TAU);
+        for i in 0..SPLIT_PIECES {
+            let angle = start + i as f32 * std::f32::consts::TAU / SPLIT_PIECES as f32;
+            let outwards = Vec3::new(angle.cos(), angle.sin(), 0.0);
+            let velocity = Velocity {
+                linvel: event.velocity + outwards * SPLIT_SPEED * rng.gen_range(0.75..1.25),
+                angvel: random_spin(rng),
+            };
+            spawn_asteroid(&mut commands, &game_assets, piece, event.translation + outwards * piece.radius(), velocity);
+        }
+    }
+}
+
+pub fn random_spin<R: Rng>(rng: &mut R) -> Vec3 {
+    Vec3::new(rng.gen_range(-MAX_SPIN..MAX_SPIN),
+              rng.gen_range(-MAX_SPIN..MAX_SPIN),
+              rng.gen_range(-MAX_SPIN..MAX_SPIN))
+}
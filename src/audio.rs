@@ -0,0 +1,425 @@
+use std::f32::consts::TAU;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use bevy::asset::io::Reader;
+use bevy::asset::{AssetLoader, AsyncReadExt, LoadContext};
+use bevy::audio::{AddAudioSource, AudioSinkPlayback, Decodable, Source, Volume};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use crate::events::{CreateEffectEvent, DamageEvent, DamageKind, PlaySoundEvent};
+use crate::game_state::InGame;
+use crate::level::LevelDefinition;
+use crate::resources::{GameAssets, Level};
+
+const SAMPLE_RATE: u32 = 44_100;
+/// Fade in and out of every note, so notes do not click.
+const NOTE_FADE: f32 = 0.005;
+/// Seconds the music of two levels overlaps.
+const CROSSFADE_TIME: f32 = 2.0;
+
+/// A sound described by its notes instead of recorded, loaded from `assets/sounds/**/*.sfx.ron`.
+#[derive(Asset, TypePath, Deserialize, Debug, Clone)]
+pub struct Synth {
+    pub wave: Wave,
+    /// played one after the other, music loops them
+    pub notes: Vec<Note>,
+    /// how fast a note dies away, per second. 0 holds it
+    #[serde(default)]
+    pub decay: f32,
+    #[serde(default = "full_volume")]
+    pub volume: f32,
+}
+
+fn full_volume() -> f32 {
+    1.0
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum Wave {
+    Sine,
+    Triangle,
+    Square,
+    Saw,
+    Noise,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct Note {
+    /// seconds
+    pub length: f32,
+    /// Hz, 0 is a rest
+    pub pitch: f32,
+    /// the pitch at the end of the note, if it slides
+    #[serde(default)]
+    pub slide: Option<f32>,
+}
+
+impl Synth {
+    pub fn duration(&self) -> f32 {
+        self.notes.iter().map(|note| note.length).sum()
+    }
+}
+
+pub struct SynthDecoder {
+    synth: Synth,
+    note: usize,
+    sample: u32,
+    phase: f32,
+    noise: u32,
+}
+
+impl Iterator for SynthDecoder {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        let note = loop {
+            let note = *self.synth.notes.get(self.note)?;
+            if self.sample < (note.length * SAMPLE_RATE as f32) as u32 {
+                break note;
+            }
+            self.note += 1;
+            self.sample = 0;
+        };
+        let time = self.sample as f32 / SAMPLE_RATE as f32;
+        self.sample += 1;
+        if note.pitch <= 0.0 {
+            return Some(0.0);
+        }
+        let pitch = note.pitch.lerp(note.slide.unwrap_or(note.pitch), time / note.length);
+        self.phase = (self.phase + pitch / SAMPLE_RATE as f32).fract();
+        let value = match self.synth.wave {
+            Wave::Sine => (self.phase * TAU).sin(),
+            Wave::Triangle => 1.0 - 4.0 * (self.phase - 0.5).abs(),
+            Wave::Square => if self.phase < 0.5 { 1.0 } else { -1.0 },
+            Wave::Saw => 2.0 * self.phase - 1.0,
+            Wave::Noise => {
+                // xorshift, the same noise every time the sound plays
+                self.noise ^= self.noise << 13;
+                self.noise ^= self.noise >> 17;
+                self.noise ^= self.noise << 5;
+                self.noise as f32 / u32::MAX as f32 * 2.0 - 1.0
+            }
+        };
+        let envelope = (-self.synth.decay * time).exp()
+            * (time / NOTE_FADE).min(1.0)
+            * ((note.length - time) / NOTE_FADE).clamp(0.0, 1.0);
+        Some(value * envelope * self.synth.volume)
+    }
+}
+
+impl Source for SynthDecoder {
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    fn channels(&self) -> u16 {
+        1
+    }
+
+    fn sample_rate(&self) -> u32 {
+        SAMPLE_RATE
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        Some(Duration::from_secs_f32(self.synth.duration()))
+    }
+}
+
+impl Decodable for Synth {
+    type DecoderItem = f32;
+    type Decoder = SynthDecoder;
+
+    fn decoder(&self) -> SynthDecoder {
+        SynthDecoder {
+            synth: self.clone(),
+            note: 0,
+            sample: 0,
+            phase: 0.0,
+            noise: 0x9e37_79b9,
+        }
+    }
+}
+
+#[derive(Default)]
+struct SynthLoader;
+
+impl AssetLoader for SynthLoader {
+    type Asset = Synth;
+    type Settings = ();
+    type Error = io::Error;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext<'_>,
+    ) -> Result<Synth, io::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        ron::de::from_bytes(&bytes).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["sfx.ron"]
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Sound {
+    Laser,
+    Explosion,
+    Hit,
+}
+
+/// Volumes between 0 and 1, kept in the user's config directory.
+#[derive(Resource, Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub struct AudioSettings {
+    pub master: f32,
+    pub music: f32,
+    pub sfx: f32,
+}
+
+impl Default for AudioSettings {
+    fn default() -> Self {
+        AudioSettings {
+            master: 0.8,
+            music: 0.6,
+            sfx: 0.8,
+        }
+    }
+}
+
+impl AudioSettings {
+    /// `<config dir>/planet-rust/audio.ron`, if the platform has a config directory.
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("planet-rust").join("audio.ron"))
+    }
+
+    pub fn load(path: &Path) -> io::Result<AudioSettings> {
+        let text = fs::read_to_string(path)?;
+        ron::from_str(&text).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let text = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        fs::write(path, text)
+    }
+
+    /// The settings from the config file, or the defaults if there is none.
+    pub fn load_or_default() -> AudioSettings {
+        let Some(path) = AudioSettings::default_path() else {
+            return AudioSettings::default();
+        };
+        match AudioSettings::load(&path) {
+            Ok(settings) => settings,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => AudioSettings::default(),
+            Err(e) => {
+                warn!("cannot read audio settings from {}: {e}", path.display());
+                AudioSettings::default()
+            }
+        }
+    }
+
+    pub fn music_volume(&self) -> f32 {
+        (self.master * self.music).clamp(0.0, 1.0)
+    }
+
+    pub fn sfx_volume(&self) -> f32 {
+        (self.master * self.sfx).clamp(0.0, 1.0)
+    }
+}
+
+/// The music of one level. Only the newest track fades in, all others fade out and go.
+#[derive(Component)]
+pub struct MusicTrack {
+    pub level: usize,
+    /// 0 silent, 1 full volume
+    pub fade: f32,
+    pub fading_in: bool,
+}
+
+/// Which level the music was last started for.
+#[derive(Resource, Default)]
+struct PlayingMusic {
+    level: Option<usize>,
+}
+
+/// What the null backend would have played, for tests.
+#[derive(Resource, Default)]
+pub struct NullAudio {
+    pub played: Vec<(Sound, Vec3)>,
+}
+
+#[derive(Default, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum AudioBackend {
+    /// plays through the `AudioPlugin` of bevy, which has to be added as well
+    #[default]
+    Rodio,
+    /// plays nothing and logs sound effects in [`NullAudio`]
+    Null,
+}
+
+/// Music per level and positional sound effects.
+pub struct GameAudioPlugin {
+    pub backend: AudioBackend,
+}
+
+impl Default for GameAudioPlugin {
+    fn default() -> Self {
+        GameAudioPlugin {
+            backend: AudioBackend::Rodio,
+        }
+    }
+}
+
+impl Plugin for GameAudioPlugin {
+    fn build(&self, app: &mut App){
+        match self.backend {
+            AudioBackend::Rodio => {
+                app.add_audio_source::<Synth>()
+                    .add_systems(Update, play_sounds.after(hear_effects).run_if(in_state(InGame)));
+            }
+            AudioBackend::Null => {
+                app.init_asset::<Synth>()
+                    .init_resource::<NullAudio>()
+                    .add_systems(Update, log_sounds.after(hear_effects).run_if(in_state(InGame)));
+            }
+        }
+        app
+            .init_asset_loader::<SynthLoader>()
+            .init_resource::<AudioSettings>()
+            .init_resource::<PlayingMusic>()
+            .add_event::<PlaySoundEvent>()
+            .add_systems(OnExit(InGame), stop_music)
+            .add_systems(Update, (hear_effects, play_level_music, fade_music).run_if(in_state(InGame)));
+    }
+}
+
+/// Explosions and hits are heard where they happen.
+fn hear_effects(
+    mut event_create_effect: EventReader<CreateEffectEvent>,
+    mut event_damage: EventReader<DamageEvent>,
+    mut event_sound: EventWriter<PlaySoundEvent>,
+    query: Query<&Transform>,
+){
+    for event in event_create_effect.read() {
+        event_sound.send(PlaySoundEvent {
+            sound: Sound::Explosion,
+            translation: event.0,
+        });
+    }
+    for event in event_damage.read() {
+        if event.kind == DamageKind::Bomb {
+            continue;
+        }
+        if let Ok(transform) = query.get(event.target) {
+            event_sound.send(PlaySoundEvent {
+                sound: Sound::Hit,
+                translation: transform.translation,
+            });
+        }
+    }
+}
+
+fn sound_handle(game_assets: &GameAssets, sound: Sound) -> Handle<Synth> {
+    match sound {
+        Sound::Laser => game_assets.laser_sound.clone(),
+        Sound::Explosion => game_assets.explosion_sound.clone(),
+        Sound::Hit => game_assets.hit_sound.clone(),
+    }
+}
+
+fn play_sounds(
+    mut commands: Commands,
+    mut event_sound: EventReader<PlaySoundEvent>,
+    game_assets: Res<GameAssets>,
+    settings: Res<AudioSettings>,
+){
+    for event in event_sound.read() {
+        commands.spawn((
+            AudioSourceBundle {
+                source: sound_handle(&game_assets, event.sound),
+                settings: PlaybackSettings::DESPAWN
+                    .with_spatial(true)
+                    .with_volume(Volume::new(settings.sfx_volume())),
+            },
+            TransformBundle::from_transform(Transform::from_translation(event.translation)),
+            StateScoped(InGame),
+        ));
+    }
+}
+
+fn log_sounds(
+    mut event_sound: EventReader<PlaySoundEvent>,
+    mut null_audio: ResMut<NullAudio>,
+){
+    null_audio.played.extend(event_sound.read().map(|event| (event.sound, event.translation)));
+}
+
+/// Starts the music of a new level, the one playing fades out.
+fn play_level_music(
+    mut commands: Commands,
+    level: Res<Level>,
+    game_assets: Res<GameAssets>,
+    level_definitions: Res<Assets<LevelDefinition>>,
+    mut playing: ResMut<PlayingMusic>,
+    mut query: Query<&mut MusicTrack>,
+){
+    if playing.level == Some(level.value) {
+        return;
+    }
+    playing.level = Some(level.value);
+    for mut track in query.iter_mut() {
+        track.fading_in = false;
+    }
+    let music = game_assets.level(&level_definitions, level.value)
+        .and_then(|definition| definition.music.as_deref())
+        .and_then(|name| game_assets.music.get(name));
+    if let Some(music) = music {
+        commands.spawn((
+            AudioSourceBundle {
+                source: music.clone(),
+                settings: PlaybackSettings::LOOP.with_volume(Volume::new(0.0)),
+            },
+            MusicTrack {
+                level: level.value,
+                fade: 0.0,
+                fading_in: true,
+            },
+            Name::new("Music"),
+            StateScoped(InGame),
+        ));
+    }
+}
+
+fn fade_music(
+    mut commands: Commands,
+    time: Res<Time>,
+    settings: Res<AudioSettings>,
+    mut query: Query<(Entity, &mut MusicTrack, Option<&AudioSink>)>,
+){
+    let step = time.delta_seconds() / CROSSFADE_TIME;
+    for (entity, mut track, sink) in query.iter_mut() {
+        track.fade = if track.fading_in { (track.fade + step).min(1.0) } else { (track.fade - step).max(0.0) };
+        if !track.fading_in && track.fade <= 0.0 {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+        if let Some(sink) = sink {
+            sink.set_volume(track.fade * settings.music_volume());
+        }
+    }
+}
+
+/// The tracks go with the game, the next one starts from the beginning.
+fn stop_music(
+    mut playing: ResMut<PlayingMusic>,
+){
+    playing.level = None;
+}
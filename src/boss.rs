@@ -0,0 +1,230 @@
+use std::f32::consts::PI;
+use bevy::prelude::*;
+use bevy::color::palettes::css::ORANGE_RED;
+use bevy_rapier3d::prelude::*;
+use crate::components::{Boss, Damage, Health, Laser, Ship, WeakPoint};
+use crate::audio::Sound;
+use crate::events::{CreateEffectEvent, DamageEvent, DamageKind, PlaySoundEvent};
+use crate::damage::RAM_DAMAGE;
+use crate::game_state::InGame;
+use crate::level::LevelDefinition;
+use crate::pool::{EntityPool, PoolCommands, SharedHandles};
+use crate::resources::{BossFight, GameAssets, GameStats, Level, Score};
+use crate::weapon::{fire_weapon, Weapon};
+
+const BOSS_SPAWN_POS: Vec3 = Vec3::new(0.0, 0.0, -300.0);
+const BOSS_FIRE_RANGE: f32 = 200.0;
+const SWAY_FREQUENCY: f32 = 0.25;
+
+/// Brings in the level's boss once the fight starts.
+pub fn spawn_boss(
+    mut commands: Commands,
+    boss_fight: Res<BossFight>,
+    level: Res<Level>,
+    level_definitions: Res<Assets<LevelDefinition>>,
+    game_assets: Res<GameAssets>,
+    query_boss: Query<(), With<Boss>>,
+){
+    if *boss_fight != BossFight::Fighting || !query_boss.is_empty() {
+        return;
+    }
+    let Some(definition) = game_assets
+        .level(&level_definitions, level.value)
+        .and_then(|definition| definition.boss.clone()) else {
+        return;
+    };
+    info!("boss {} appears", definition.name);
+
+    // the boss and the lasers are both kinematic, so their contacts have to be switched on
+    let collision_types = ActiveCollisionTypes::default() | ActiveCollisionTypes::KINEMATIC_KINEMATIC;
+    commands.spawn(SceneBundle {
+        scene: game_assets.tower_scene.clone(),
+        transform: Transform {
+            translation: BOSS_SPAWN_POS,
+            rotation: Quat::from_rotation_x(PI * 0.5),
+            scale: Vec3::splat(2.0),
+        },
+        ..default()
+    })
+        .insert(RigidBody::KinematicVelocityBased)
+        .insert(Velocity::linear(Vec3::Z * definition.speed))
+        .insert(Collider::cuboid(2.5, 2.5, 1.5))
+        .insert(ActiveEvents::COLLISION_EVENTS)
+        .insert(collision_types)
+        .insert(Name::new(definition.name.clone()))
+        .with_children(|parent| {
+            for weak_point in &definition.weak_points {
+                let (x, y, z) = weak_point.offset;
+                parent.spawn(SceneBundle {
+                    scene: game_assets.tower_scene.clone(),
+                    // the offsets are in world units, undo the parent's scale and rotation
+                    transform: Transform {
+                        translation: Quat::from_rotation_x(-PI * 0.5) * Vec3::new(x, y, z) / 2.0,
+                        scale: Vec3::splat(0.3),
+                        ..default()
+                    },
+                    ..default()
+                })
+                    .insert(Collider::ball(2.0))
+                    .insert(ActiveEvents::COLLISION_EVENTS)
+                    .insert(collision_types)
+                    .insert(Name::new("WeakPoint"))
+                    .insert(Health::new(weak_point.hits as f32))
+                    .insert(WeakPoint);
+            }
+        })
+        .insert(Health::new(definition.core_hits as f32))
+        .insert(Boss {
+            phase: 0,
+            cooldown: definition.phases.first().map_or(1.0, |phase| phase.cooldown),
+            age: 0.0,
+            definition,
+        })
+        .insert(StateScoped(InGame));
+}
+
+/// Flies in, holds its distance and sways sideways as its phase says.
+pub fn move_boss(
+    time: Res<Time>,
+    mut query: Query<(&Transform, &mut Velocity, &mut Boss)>,
+){
+    for (transform, mut velocity, mut boss) in query.iter_mut() {
+        boss.age += time.delta_seconds();
+        velocity.linvel.z = if transform.translation.z < boss.definition.hold_z {
+            boss.definition.speed
+        } else {
+            0.0
+        };
+        let sway = boss.definition.phases.get(boss.phase).map_or(0.0, |phase| phase.sway);
+        let omega = SWAY_FREQUENCY * 2.0 * PI;
+        velocity.linvel.x = sway * omega * (boss.age * omega).cos();
+    }
+}
+
+pub fn boss_attack(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut pool: ResMut<EntityPool>,
+    mut handles: ResMut<SharedHandles>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut event_sound: EventWriter<PlaySoundEvent>,
+    query_ship: Query<&Transform, With<Ship>>,
+    mut query: Query<(&Transform, &mut Boss)>,
+){
+    let Ok(ship) = query_ship.get_single() else {
+        return;
+    };
+    for (transform, mut boss) in query.iter_mut() {
+        boss.cooldown -= time.delta_seconds();
+        if boss.cooldown > 0.0 || transform.translation.z.abs() > BOSS_FIRE_RANGE {
+            continue;
+        }
+        let Some(phase) = boss.definition.phases.get(boss.phase) else {
+            continue;
+        };
+        let muzzle = transform.translation + Vec3::Z * 6.0;
+        let aim = (ship.translation - muzzle).normalize_or(Vec3::Z);
+        for direction in phase.attack.directions(aim, boss.age) {
+            fire_weapon(&mut commands, &mut pool, &mut handles, &mut materials, Weapon::Laser, 0.0,
+                        muzzle, direction, Color::Srgba(ORANGE_RED), false);
+        }
+        event_sound.send(PlaySoundEvent {
+            sound: Sound::Laser,
+            translation: muzzle,
+        });
+        boss.cooldown = phase.cooldown;
+    }
+}
+
+/// Player lasers wear down the weak points first, then the core, both through [`DamageEvent`]s.
+/// Touching the boss costs shields.
+pub fn handle_boss_hits(
+    mut commands: Commands,
+    mut collision_events: EventReader<CollisionEvent>,
+    mut event_create_effect: EventWriter<CreateEffectEvent>,
+    mut event_damage: EventWriter<DamageEvent>,
+    mut stats: ResMut<GameStats>,
+    query_laser: Query<(&Laser, &Damage)>,
+    query_ship: Query<Entity, With<Ship>>,
+    query_weak_point: Query<&Health, With<WeakPoint>>,
+    query_boss: Query<(Entity, &Transform), With<Boss>>,
+){
+    let Ok((boss_entity, boss_transform)) = query_boss.get_single() else {
+        return;
+    };
+    let ship_entity = query_ship.single();
+    let shielded = query_weak_point.iter().any(Health::is_alive);
+
+    for collision_event in collision_events.read() {
+        let CollisionEvent::Started(e1, e2, _) = collision_event else {
+            continue;
+        };
+        for (part, other) in [(*e1, *e2), (*e2, *e1)] {
+            let is_core = part == boss_entity;
+            if !is_core && !query_weak_point.contains(part) {
+                continue;
+            }
+            if other == ship_entity {
+                event_damage.send(DamageEvent {
+                    target: ship_entity,
+                    amount: RAM_DAMAGE,
+                    kind: DamageKind::Ram,
+                });
+                event_create_effect.send(CreateEffectEvent(boss_transform.translation, 1.0));
+                continue;
+            }
+            let Some(damage) = query_laser.get(other).ok()
+                .filter(|(laser, _)| laser.player)
+                .map(|(_, damage)| damage.amount) else {
+                continue;
+            };
+            commands.release(other);
+            stats.shots_hit += 1;
+            if is_core && shielded {
+                continue;
+            }
+            event_damage.send(DamageEvent {
+                target: part,
+                amount: damage,
+                kind: DamageKind::Projectile,
+            });
+        }
+    }
+}
+
+/// After [`crate::damage::apply_damage`]: shot off weak points fall away, the core's
+/// health picks the phase and the boss is scored once it is used up.
+pub fn destroy_boss_parts(
+    mut commands: Commands,
+    mut event_create_effect: EventWriter<CreateEffectEvent>,
+    mut boss_fight: ResMut<BossFight>,
+    mut stats: ResMut<GameStats>,
+    mut score: ResMut<Score>,
+    query_weak_point: Query<(Entity, &GlobalTransform, &Health), With<WeakPoint>>,
+    mut query_boss: Query<(Entity, &Transform, &Health, &mut Boss)>,
+){
+    for (entity, transform, health) in query_weak_point.iter() {
+        if !health.is_alive() {
+            event_create_effect.send(CreateEffectEvent(transform.translation(), 1.5));
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+    let Ok((boss_entity, boss_transform, health, mut boss)) = query_boss.get_single_mut() else {
+        return;
+    };
+    let phase = boss.definition.phase(health.fraction());
+    if phase != boss.phase {
+        info!("boss {} enters phase {}", boss.definition.name, phase + 1);
+        boss.phase = phase;
+    }
+    if !health.is_alive() {
+        info!("boss {} destroyed", boss.definition.name);
+        stats.kills += 1;
+        score.add_kill(boss.definition.points);
+        for offset in [Vec3::ZERO, Vec3::X * 4.0, Vec3::NEG_X * 4.0, Vec3::Y * 4.0] {
+            event_create_effect.send(CreateEffectEvent(boss_transform.translation + offset, 3.0));
+        }
+        commands.entity(boss_entity).despawn_recursive();
+        *boss_fight = BossFight::Defeated;
+    }
+}
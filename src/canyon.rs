@@ -0,0 +1,167 @@
+use bevy::prelude::*;
+use bevy::render::mesh::{Indices, PrimitiveTopology};
+use bevy::render::render_asset::RenderAssetUsages;
+use bevy_rapier3d::prelude::*;
+use rand::Rng;
+
+/// Half the width of the canyon from rim to rim.
+const RIM_X: f32 = 60.0;
+/// How far a wall reaches out from the edge of the floor to the rim.
+const WALL_RUN: f32 = 6.0;
+/// One texture repeat, the size of the old tiles.
+const TILE_SIZE: f32 = 10.0;
+
+/// The shape of the canyon at one cross-section.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Profile {
+    /// from the center to the foot of a wall
+    pub half_width: f32,
+    /// floor height in the center
+    pub floor: f32,
+    /// how much higher the left edge of the floor is than the center, the right is as much lower
+    pub bank: f32,
+    /// wall height above the floor
+    pub depth: f32,
+}
+
+impl Default for Profile {
+    /// The cross-section of the old fixed valley.
+    fn default() -> Self {
+        Profile {
+            half_width: 15.0,
+            floor: -20.0,
+            bank: 0.0,
+            depth: 20.0,
+        }
+    }
+}
+
+impl Profile {
+    fn random<R: Rng>(rng: &mut R) -> Profile {
+        Profile {
+            half_width: rng.gen_range(9.0..18.0),
+            floor: rng.gen_range(-22.0..-12.0),
+            bank: rng.gen_range(-4.0..4.0),
+            depth: rng.gen_range(14.0..26.0),
+        }
+    }
+
+    /// Moves towards `target` by at most one step per value.
+    fn step_towards(&self, target: &Profile) -> Profile {
+        let step = |from: f32, to: f32, max: f32| from + (to - from).clamp(-max, max);
+        Profile {
+            half_width: step(self.half_width, target.half_width, 0.6),
+            floor: step(self.floor, target.floor, 0.5),
+            bank: step(self.bank, target.bank, 0.3),
+            depth: step(self.depth, target.depth, 1.0),
+        }
+    }
+
+    pub fn lerp(&self, other: &Profile, t: f32) -> Profile {
+        Profile {
+            half_width: self.half_width.lerp(other.half_width, t),
+            floor: self.floor.lerp(other.floor, t),
+            bank: self.bank.lerp(other.bank, t),
+            depth: self.depth.lerp(other.depth, t),
+        }
+    }
+
+    /// Height of the floor at `x`, inside the walls.
+    pub fn floor_at(&self, x: f32) -> f32 {
+        self.floor - self.bank * x / self.half_width
+    }
+
+    /// The cross-section from the left rim to the right one.
+    fn points(&self) -> [Vec2; 7] {
+        let left = self.floor + self.bank;
+        let right = self.floor - self.bank;
+        [
+            Vec2::new(-RIM_X, left + self.depth),
+            Vec2::new(-self.half_width - WALL_RUN, left + self.depth),
+            Vec2::new(-self.half_width, left),
+            Vec2::new(0.0, self.floor),
+            Vec2::new(self.half_width, right),
+            Vec2::new(self.half_width + WALL_RUN, right + self.depth),
+            Vec2::new(RIM_X, right + self.depth),
+        ]
+    }
+}
+
+/// Builds the canyon one segment at a time: it keeps heading for a random target shape
+/// and picks a new one once it got there, so neighbouring segments always fit together.
+#[derive(Resource, Default)]
+pub struct Canyon {
+    profile: Profile,
+    target: Profile,
+}
+
+impl Canyon {
+    /// The cross-sections at the near and the far end of the next segment.
+    pub fn next_segment<R: Rng>(&mut self, rng: &mut R) -> (Profile, Profile) {
+        if self.profile == self.target {
+            self.target = Profile::random(rng);
+        }
+        let near = self.profile;
+        self.profile = near.step_towards(&self.target);
+        (near, self.profile)
+    }
+}
+
+/// One segment of the canyon as a single mesh, `near` at `+length / 2` and `far` at `-length / 2`.
+pub struct Segment {
+    positions: Vec<Vec3>,
+    normals: Vec<Vec3>,
+    uvs: Vec<Vec2>,
+    triangles: Vec<[u32; 3]>,
+}
+
+impl Segment {
+    pub fn new(near: &Profile, far: &Profile, length: f32) -> Segment {
+        let mut segment = Segment {
+            positions: Vec::new(),
+            normals: Vec::new(),
+            uvs: Vec::new(),
+            triangles: Vec::new(),
+        };
+        let (near_z, far_z) = (length / 2.0, -length / 2.0);
+        let (near, far) = (near.points(), far.points());
+        for i in 0..near.len() - 1 {
+            // long stretches are cut into tile sized quads, so each shows the texture once
+            let pieces = (near[i].distance(near[i + 1]) / TILE_SIZE).ceil().max(1.0) as usize;
+            for piece in 0..pieces {
+                let t0 = piece as f32 / pieces as f32;
+                let t1 = (piece + 1) as f32 / pieces as f32;
+                segment.add_quad([
+                    near[i].lerp(near[i + 1], t0).extend(near_z),
+                    near[i].lerp(near[i + 1], t1).extend(near_z),
+                    far[i].lerp(far[i + 1], t1).extend(far_z),
+                    far[i].lerp(far[i + 1], t0).extend(far_z),
+                ]);
+            }
+        }
+        segment
+    }
+
+    fn add_quad(&mut self, corners: [Vec3; 4]) {
+        let [a, b, _, d] = corners;
+        let normal = (b - a).cross(d - a).normalize_or_zero();
+        let first = self.positions.len() as u32;
+        self.positions.extend(corners);
+        self.normals.extend([normal; 4]);
+        self.uvs.extend([Vec2::new(0.0, 0.0), Vec2::new(1.0, 0.0), Vec2::new(1.0, 1.0), Vec2::new(0.0, 1.0)]);
+        self.triangles.push([first, first + 1, first + 2]);
+        self.triangles.push([first, first + 2, first + 3]);
+    }
+
+    pub fn mesh(&self) -> Mesh {
+        Mesh::new(PrimitiveTopology::TriangleList, RenderAssetUsages::default())
+            .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, self.positions.clone())
+            .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, self.normals.clone())
+            .with_inserted_attribute(Mesh::ATTRIBUTE_UV_0, self.uvs.clone())
+            .with_inserted_indices(Indices::U32(self.triangles.iter().flatten().copied().collect()))
+    }
+
+    pub fn collider(&self) -> Collider {
+        Collider::trimesh(self.positions.clone(), self.triangles.clone())
+    }
+}
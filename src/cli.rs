@@ -0,0 +1,69 @@
+use std::path::PathBuf;
+use bevy::window::{Window, WindowMode};
+use clap::{ArgGroup, Parser};
+use crate::settings::Resolution;
+
+/// The command line of the game, for reproducible test runs.
+#[derive(Parser, Debug)]
+#[command(name = "planet-rust", version, about = "Defend Planet Rust against the Xplusians and the Pythones")]
+#[command(group(ArgGroup::new("length").args(["replay", "frames"]).multiple(true)))]
+pub struct Cli {
+    /// Level every game starts at. A replay brings its own
+    #[arg(long, default_value_t = 1, value_parser = parse_level, conflicts_with = "replay")]
+    pub level: usize,
+    /// Seed of all randomness, otherwise a random one is logged. A replay brings its own
+    #[arg(long, conflicts_with = "replay")]
+    pub seed: Option<u64>,
+    /// Fill the screen, instead of what the settings say
+    #[arg(long, conflicts_with = "windowed")]
+    pub fullscreen: bool,
+    /// Play in a window, instead of what the settings say
+    #[arg(long)]
+    pub windowed: bool,
+    /// Size of the window, e.g. 1280x720, instead of what the settings say
+    #[arg(long)]
+    pub resolution: Option<Resolution>,
+    /// Write the seed and the input of every frame to a replay file
+    #[arg(long, value_name = "FILE", conflicts_with = "replay")]
+    pub record: Option<PathBuf>,
+    /// Play a replay file back instead of the keyboard
+    #[arg(long, value_name = "FILE")]
+    pub replay: Option<PathBuf>,
+    /// Play without a window and print where the game ended, needs --replay or --frames
+    #[arg(long, requires = "length", conflicts_with_all = ["record", "fullscreen", "windowed"])]
+    pub headless: bool,
+    /// Frames to play headless at most
+    #[arg(long, requires = "headless")]
+    pub frames: Option<usize>,
+    /// Add the debug tools: colliders, orbit camera, inspector and console
+    #[cfg(feature = "debug")]
+    #[arg(long)]
+    pub debug: bool,
+    /// Run the console commands in the file at the start of every game
+    #[cfg(feature = "debug")]
+    #[arg(long, value_name = "FILE", requires = "debug")]
+    pub script: Option<PathBuf>,
+}
+
+fn parse_level(s: &str) -> Result<usize, String> {
+    match s.parse() {
+        Ok(level) if level > 0 => Ok(level),
+        _ => Err(format!("invalid level {s}, the first one is 1")),
+    }
+}
+
+impl Cli {
+    /// The window flags win over the saved settings for this run only, so they change
+    /// the window and not the [`crate::settings::Settings`] that the options menu saves.
+    pub fn apply(&self, window: &mut Window) {
+        if self.fullscreen {
+            window.mode = WindowMode::BorderlessFullscreen;
+        }
+        if self.windowed {
+            window.mode = WindowMode::Windowed;
+        }
+        if let Some(resolution) = self.resolution {
+            window.resolution.set(resolution.width as f32, resolution.height as f32);
+        }
+    }
+}
@@ -1,93 +1,223 @@
 use bevy::prelude::*;
 use bevy_rapier3d::prelude::*;
-use crate::components::{Ship, Opponent, Laser};
-use crate::events::CreateEffectEvent;
+use crate::components::{Ship, Opponent, Laser, Terrain, PowerUp, PowerUpKind, Damage, Health, Asteroid};
+use crate::damage::SHIP_RAM_DAMAGE;
+use crate::events::{CreateEffectEvent, DamageEvent, DamageKind};
+use crate::pool::PoolCommands;
+use crate::powerup::{POWER_UP_TIME, SHIELD_RECHARGE};
+use crate::resources::{ActivePowerUps, GameStats};
 
+/// Slower crashes between asteroids only bounce.
+pub const CHIP_SPEED: f32 = 10.0;
+
+/// Turns the contacts of the frame into [`DamageEvent`]s, `apply_damage` decides what they do.
 pub fn handle_collisions(
     mut collision_events: EventReader<CollisionEvent>,
-    mut query_opponent: Query<(Entity, &Transform, &mut Opponent)>,
-    query_laser: Query<(Entity, &Transform, &Laser)>,
-    mut query_ship: Query<(Entity, &mut Ship)>,
+    query_opponent: Query<Entity, With<Opponent>>,
+    query_laser: Query<(Entity, &Laser)>,
+    query_terrain: Query<(), With<Terrain>>,
+    query_power_up: Query<(Entity, &PowerUp)>,
+    query_damage: Query<&Damage>,
+    query_asteroid: Query<(&Asteroid, &Velocity)>,
+    query_projectile: Query<(&Laser, &Transform, &Velocity)>,
+    mut query_impulse: Query<(&Transform, &mut ExternalImpulse)>,
+    mut query_ship: Query<(Entity, &Transform, &mut Health), With<Ship>>,
     mut event_create_effect: EventWriter<CreateEffectEvent>,
+    mut event_damage: EventWriter<DamageEvent>,
+    mut stats: ResMut<GameStats>,
+    mut active_power_ups: ResMut<ActivePowerUps>,
     mut commands: Commands,
 ) {
-    let (ship_entity, mut ship) = query_ship.single_mut();
+    let (ship_entity, ship_transform, mut ship_health) = query_ship.single_mut();
 
     for collision_event in collision_events.read() {
         if let CollisionEvent::Started(e1, e2, _) = collision_event {
-            handle_collision(*e1, *e2, &ship_entity, &mut ship, &mut query_opponent, &query_laser, &mut event_create_effect, &mut commands);
+            let damage = |entity: Entity| query_damage.get(entity).map_or(0.0, |damage| damage.amount);
+            if let Some((power_up_entity, power_up)) = query_power_up.iter().find(|(e, _)| *e == *e1 || *e == *e2) {
+                // pickups are sensors, only the ship can collect them
+                if *e1 == ship_entity || *e2 == ship_entity {
+                    commands.entity(power_up_entity).despawn_recursive();
+                    handle_power_up_collision(power_up.kind, &mut ship_health, &mut active_power_ups,
+                                              &query_opponent, &mut event_damage);
+                }
+                continue;
+            }
+            if let Some(terrain) = [*e1, *e2].into_iter().find(|e| query_terrain.contains(*e)) {
+                let other = if terrain == *e1 { *e2 } else { *e1 };
+                handle_terrain_collision(other, damage(terrain), ship_entity, ship_transform, &query_laser,
+                                         &mut event_create_effect, &mut event_damage, &mut commands);
+                continue;
+            }
+            if let (Ok(asteroid_1), Ok(asteroid_2)) = (query_asteroid.get(*e1), query_asteroid.get(*e2)) {
+                handle_asteroid_collision(*e1, asteroid_1, *e2, asteroid_2, &mut event_damage);
+                continue;
+            }
+            if let Some(opponent_entity) = query_opponent.iter().find(|e| *e == *e1 || *e == *e2) {
+                let other = if opponent_entity == *e1 { *e2 } else { *e1 };
+                if other == ship_entity {
+                    handle_ship_opponent_collision(ship_entity, opponent_entity, damage(opponent_entity), &mut event_damage);
+                } else {
+                    push(other, opponent_entity, &query_projectile, &mut query_impulse);
+                    handle_laser_opponent_collision(other, opponent_entity, damage(other), &query_laser,
+                                                    &mut event_damage, &mut stats, &mut commands);
+                }
+            } else if *e1 == ship_entity || *e2 == ship_entity {
+                let other = if *e1 == ship_entity { *e2 } else { *e1 };
+                handle_laser_ship_collision(other, ship_entity, damage(other), &query_laser, &mut event_damage, &mut commands);
+            }
         }
     }
 }
 
-fn handle_collision(
-    e1: Entity,
-    e2: Entity,
-    ship_entity: &Entity,
-    ship: &mut Ship,
-    query_opponent: &mut Query<(Entity, &Transform, &mut Opponent)>,
-    query_laser: &Query<(Entity, &Transform, &Laser)>,
+/// The valley stops lasers, and the ship loses shields when it scrapes along.
+fn handle_terrain_collision(
+    other: Entity,
+    damage: f32,
+    ship_entity: Entity,
+    ship_transform: &Transform,
+    query_laser: &Query<(Entity, &Laser)>,
     event_create_effect: &mut EventWriter<CreateEffectEvent>,
+    event_damage: &mut EventWriter<DamageEvent>,
     commands: &mut Commands,
 ) {
-    if let Some((opponent_entity, opponent_transform, mut opponent)) = query_opponent.iter_mut().find(|(e, _, _)| *e == e1 || *e == e2) {
-        if e1 == *ship_entity || e2 == *ship_entity {
-            handle_ship_opponent_collision(ship, opponent_entity, opponent_transform, event_create_effect, commands);
-        } else {
-            handle_laser_opponent_collision(e1, e2, &opponent_entity, &mut opponent, opponent_transform, query_laser, ship, event_create_effect, commands);
+    if other == ship_entity {
+        event_damage.send(DamageEvent {
+            target: ship_entity,
+            amount: damage,
+            kind: DamageKind::Terrain,
+        });
+        event_create_effect.send(CreateEffectEvent(ship_transform.translation, 0.5));
+    } else if query_laser.contains(other) {
+        commands.release(other);
+    }
+}
+
+/// Shields are recharged at once, the gun power-ups start (or restart) their timer
+/// and a bomb destroys every opponent still flying.
+fn handle_power_up_collision(
+    kind: PowerUpKind,
+    ship_health: &mut Health,
+    active_power_ups: &mut ActivePowerUps,
+    query_opponent: &Query<Entity, With<Opponent>>,
+    event_damage: &mut EventWriter<DamageEvent>,
+) {
+    match kind {
+        PowerUpKind::Shield => {
+            let recharge = ship_health.max * SHIELD_RECHARGE;
+            ship_health.heal(recharge);
+        }
+        PowerUpKind::SpreadShot => active_power_ups.spread_shot = POWER_UP_TIME,
+        PowerUpKind::RapidFire => active_power_ups.rapid_fire = POWER_UP_TIME,
+        PowerUpKind::Bomb => {
+            for opponent_entity in query_opponent.iter() {
+                event_damage.send(DamageEvent {
+                    target: opponent_entity,
+                    amount: f32::MAX,
+                    kind: DamageKind::Bomb,
+                });
+            }
+        }
+    }
+}
+
+/// Rapier makes them bounce off, on top they chip pieces off each other
+/// when they crash hard enough.
+fn handle_asteroid_collision(
+    e1: Entity,
+    (asteroid_1, velocity_1): (&Asteroid, &Velocity),
+    e2: Entity,
+    (asteroid_2, velocity_2): (&Asteroid, &Velocity),
+    event_damage: &mut EventWriter<DamageEvent>,
+) {
+    if (velocity_1.linvel - velocity_2.linvel).length() < CHIP_SPEED {
+        return;
+    }
+    for (target, amount) in [(e1, asteroid_2.chip_damage), (e2, asteroid_1.chip_damage)] {
+        if amount > 0.0 {
+            event_damage.send(DamageEvent {
+                target,
+                amount,
+                kind: DamageKind::Chip,
+            });
         }
-    } else if e1 == *ship_entity || e2 == *ship_entity {
-        handle_laser_ship_collision(e1, e2, ship, query_laser, commands);
     }
 }
 
+/// A player projectile gives what it hits its momentum, off centre hits make it spin.
+fn push(
+    projectile: Entity,
+    target: Entity,
+    query_projectile: &Query<(&Laser, &Transform, &Velocity)>,
+    query_impulse: &mut Query<(&Transform, &mut ExternalImpulse)>,
+) {
+    let Ok((laser, projectile_transform, projectile_velocity)) = query_projectile.get(projectile) else {
+        return;
+    };
+    if !laser.player {
+        return;
+    }
+    if let Ok((target_transform, mut impulse)) = query_impulse.get_mut(target) {
+        let momentum = projectile_velocity.linvel * laser.mass;
+        impulse.impulse += momentum;
+        impulse.torque_impulse += (projectile_transform.translation - target_transform.translation).cross(momentum);
+    }
+}
+
+/// Both take damage, `apply_damage` destroys the opponent if it is used up.
 fn handle_ship_opponent_collision(
-    ship: &mut Ship,
+    ship_entity: Entity,
     opponent_entity: Entity,
-    opponent_transform: &Transform,
-    event_create_effect: &mut EventWriter<CreateEffectEvent>,
-    commands: &mut Commands,
+    damage: f32,
+    event_damage: &mut EventWriter<DamageEvent>,
 ) {
-    ship.shields -= 0.10;
-    event_create_effect.send(CreateEffectEvent(opponent_transform.translation));
-    commands.entity(opponent_entity).despawn_recursive();
+    event_damage.send_batch([
+        DamageEvent {
+            target: ship_entity,
+            amount: damage,
+            kind: DamageKind::Ram,
+        },
+        DamageEvent {
+            target: opponent_entity,
+            amount: SHIP_RAM_DAMAGE,
+            kind: DamageKind::Ram,
+        },
+    ]);
 }
 
 fn handle_laser_opponent_collision(
-    e1: Entity,
-    e2: Entity,
-    opponent_entity: &Entity,
-    opponent: &mut Opponent,
-    opponent_transform: &Transform,
-    query_laser: &Query<(Entity, &Transform, &Laser)>,
-    ship: &mut Ship,
-    event_create_effect: &mut EventWriter<CreateEffectEvent>,
+    other: Entity,
+    opponent_entity: Entity,
+    damage: f32,
+    query_laser: &Query<(Entity, &Laser)>,
+    event_damage: &mut EventWriter<DamageEvent>,
+    stats: &mut GameStats,
     commands: &mut Commands,
 ) {
-    if let Some((laser_entity, _, laser)) = query_laser.iter().find(|(e, _, _)| *e == e1 || *e == e2) {
-        if laser.player {
-            opponent.max_hits -= 1;
-            if opponent.max_hits <= 0 {
-                ship.hits -= 1;
-                event_create_effect.send(CreateEffectEvent(opponent_transform.translation));
-                commands.entity(laser_entity).despawn_recursive();
-                commands.entity(*opponent_entity).despawn_recursive();
-            }
-        }
+    if query_laser.get(other).is_ok_and(|(_, laser)| laser.player) {
+        stats.shots_hit += 1;
+        event_damage.send(DamageEvent {
+            target: opponent_entity,
+            amount: damage,
+            kind: DamageKind::Projectile,
+        });
+        commands.release(other);
     }
 }
 
 fn handle_laser_ship_collision(
-    e1: Entity,
-    e2: Entity,
-    ship: &mut Ship,
-    query_laser: &Query<(Entity, &Transform, &Laser)>,
+    other: Entity,
+    ship_entity: Entity,
+    damage: f32,
+    query_laser: &Query<(Entity, &Laser)>,
+    event_damage: &mut EventWriter<DamageEvent>,
     commands: &mut Commands,
 ) {
-    if let Some((laser_entity, _, laser)) = query_laser.iter().find(|(e, _, _)| *e == e1 || *e == e2) {
-        if !laser.player {
-            ship.shields -= 0.05;
-            commands.entity(laser_entity).despawn_recursive();
-        }
+    if query_laser.get(other).is_ok_and(|(_, laser)| !laser.player) {
+        event_damage.send(DamageEvent {
+            target: ship_entity,
+            amount: damage,
+            kind: DamageKind::Projectile,
+        });
+        commands.release(other);
     }
-}
\ No newline at end of file
+}
@@ -1,4 +1,7 @@
 use bevy::prelude::*;
+use serde::Deserialize;
+use crate::level::BossDefinition;
+use crate::weapon::Weapon;
 
 #[derive(Component)]
 pub struct Despawnable {
@@ -9,41 +12,226 @@ pub struct Despawnable {
 #[derive(Component)]
 pub struct Planet;
 
-#[derive(PartialEq)]
+/// The camera the game is seen through, the debug build can switch to an orbit camera.
+#[derive(Component)]
+pub struct MainCamera;
+
+/// Canyon segments of the tower run, flying into them costs shields.
+#[derive(Component)]
+pub struct Terrain;
+
+/// A gun tower standing in the valley, also an [`Opponent`].
+#[derive(Component)]
+pub struct Tower;
+
+#[derive(PartialEq, Debug)]
 pub enum WinOrLostState {
     Win,
     Lost,
     Neutral,
 }
 
-#[derive(Component)]
+/// The player, its shields are its [`Health`].
+#[derive(Component, Debug)]
 pub struct Ship {
-    pub shields: f32,
     pub hits: i32,
     pub win_or_lost: WinOrLostState,
 }
 
-#[derive(Component)]
+/// Fires the gun's weapon from each of its positions.
+#[derive(Component, Debug)]
 pub struct LaserGun {
+    pub weapon: Weapon,
     pub positions: Vec<Vec3>,
     pub color: Color,
     pub player: bool,
     pub fire: bool,
     pub cooldown: f32,
     pub std_cooldown: f32,
+    /// shot direction instead of straight ahead, for opponents that aim
+    pub aim: Option<Vec3>,
+    /// seconds the charged beam has been charging
+    pub charge: f32,
 }
 
-#[derive(Component)]
+/// Radius of the rock model at scale 1.
+const ASTEROID_MODEL_RADIUS: f32 = 0.5;
+const ASTEROID_DENSITY: f32 = 0.5;
+/// How much smaller each piece of a split asteroid is.
+const ASTEROID_SPLIT_FACTOR: f32 = 0.5;
+
+/// A rock of the asteroid field. Its collider, mass, health and points all follow from `scale`.
+#[derive(Component, Clone, Copy, PartialEq, Debug)]
+pub struct Asteroid {
+    pub scale: f32,
+    /// hit points per unit of scale
+    pub toughness: f32,
+    /// points for the smallest asteroid, bigger ones are easier to hit and give less
+    pub points: u32,
+    /// the smallest scale of the level, pieces below it are not split off
+    pub min_scale: f32,
+    /// what it does to another asteroid it crashes into, 0 to just bounce off
+    pub chip_damage: f32,
+}
+
+impl Asteroid {
+    pub fn radius(&self) -> f32 {
+        self.scale * ASTEROID_MODEL_RADIUS
+    }
+
+    pub fn mass(&self) -> f32 {
+        ASTEROID_DENSITY * 4.0 / 3.0 * std::f32::consts::PI * self.radius().powi(3)
+    }
+
+    pub fn health(&self) -> f32 {
+        (self.scale * self.toughness).round().max(1.0)
+    }
+
+    pub fn score(&self) -> u32 {
+        (self.points as f32 * self.min_scale / self.scale).round().max(1.0) as u32
+    }
+
+    /// What one piece looks like once this asteroid breaks, `None` if it is too small to split.
+    pub fn piece(&self) -> Option<Asteroid> {
+        let scale = self.scale * ASTEROID_SPLIT_FACTOR;
+        (scale >= self.min_scale).then_some(Asteroid {
+            scale,
+            ..*self
+        })
+    }
+}
+
+/// Anything the player scores for, it is destroyed once its [`Health`] is used up.
+#[derive(Component, Debug)]
 pub struct Opponent {
-    pub max_hits: i32,
+    pub points: u32,
+}
+
+/// What is left before the owner is destroyed. All damage is in the same units,
+/// one laser hit does 1.
+#[derive(Component, Clone, Copy, PartialEq, Debug)]
+pub struct Health {
+    pub current: f32,
+    pub max: f32,
+}
+
+impl Health {
+    pub fn new(max: f32) -> Self {
+        Health {
+            current: max,
+            max,
+        }
+    }
+
+    pub fn fraction(&self) -> f32 {
+        self.current / self.max
+    }
+
+    pub fn is_alive(&self) -> bool {
+        self.current > 0.0
+    }
+
+    /// Gives back up to `amount`, never more than `max`. Damage goes the other way
+    /// through [`crate::events::DamageEvent`].
+    pub fn heal(&mut self, amount: f32) {
+        self.current = (self.current + amount).min(self.max);
+    }
+}
+
+/// What the owner does to whatever it hits: projectiles, ramming opponents and the valley walls.
+#[derive(Component, Clone, Copy, PartialEq, Debug)]
+pub struct Damage {
+    pub amount: f32,
 }
 
+/// Takes away a share of every hit, from 0 (none) to 1 (all).
+#[derive(Component)]
+pub struct Armour {
+    pub reduction: f32,
+}
+
+/// Soaks up damage before the armour and the [`Health`], recharging `recharge` per second.
+#[derive(Component)]
+pub struct Shield {
+    pub current: f32,
+    pub max: f32,
+    pub recharge: f32,
+}
+
+/// After a hit the owner cannot be hurt again for `duration` seconds.
+#[derive(Component)]
+pub struct Invulnerability {
+    pub duration: f32,
+    pub remaining: f32,
+}
+
+/// How a fighter flies, chosen per spawn entry in the level file.
+#[derive(Deserialize, Clone, Copy, PartialEq, Debug, Default)]
+pub enum Flight {
+    #[default]
+    Straight,
+    /// sideways at `speed`, turning at the edges of the play area
+    Strafe { speed: f32 },
+    /// sine wave around the spawn position, `frequency` in waves per second
+    Weave { amplitude: f32, frequency: f32 },
+    /// `count` fighters in a V, `spacing` apart
+    Formation { count: usize, spacing: f32 },
+    /// turns towards the ship, `turn_rate` is how much of the turn it makes per second
+    Kamikaze { turn_rate: f32 },
+}
+
+#[derive(Component)]
+pub struct OpponentBehaviour {
+    pub flight: Flight,
+    /// shoots where the ship will be instead of straight ahead
+    pub lead_shots: bool,
+    pub age: f32,
+    pub origin: Vec3,
+}
+
+/// The core of a boss, its [`Health`] only goes down once the weak points are gone.
+#[derive(Component)]
+pub struct Boss {
+    pub definition: BossDefinition,
+    pub phase: usize,
+    pub cooldown: f32,
+    pub age: f32,
+}
+
+/// A part of a boss with its own collider and [`Health`], shielding the core while it stands.
+#[derive(Component)]
+pub struct WeakPoint;
+
+/// What a pickup does once the ship collects it.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum PowerUpKind {
+    /// recharges part of the shields
+    Shield,
+    /// two more guns for a while
+    SpreadShot,
+    /// shorter cooldown for a while
+    RapidFire,
+    /// destroys every opponent in sight
+    Bomb,
+}
+
+/// A pickup dropped by a destroyed opponent, it flies towards the ship.
+#[derive(Component)]
+pub struct PowerUp {
+    pub kind: PowerUpKind,
+}
+
+/// Any projectile, whatever weapon fired it. Its [`Damage`] says how hard it hits,
+/// its `mass` how hard it pushes.
 #[derive(Component)]
 pub struct Laser {
     pub player: bool,
+    pub mass: f32,
 }
 
+/// A projectile that turns towards its target, `turn_rate` like [`Flight::Kamikaze`].
 #[derive(Component)]
-pub struct EffectTime {
-    pub timer: Timer,
-}
\ No newline at end of file
+pub struct Homing {
+    pub turn_rate: f32,
+}
+
@@ -0,0 +1,239 @@
+use std::collections::VecDeque;
+use std::fs;
+use std::path::PathBuf;
+use std::str::FromStr;
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use crate::components::{Health, Invulnerability, Opponent, Ship};
+use crate::events::{DamageEvent, DamageKind, SpawnOpponentEvent};
+use crate::game_state::{GameState, InGame};
+use crate::gameplay::{LevelEntry, PlayerInputSet};
+use crate::level::{LevelDefinition, SpawnKind};
+use crate::rng::GameRng;
+use crate::save::SavingDisabled;
+
+/// Lines the console keeps, older ones are dropped.
+const MAX_LOG: usize = 200;
+
+/// A line of the console or of its startup script.
+#[derive(Clone, PartialEq, Debug)]
+pub enum ConsoleCommand {
+    /// `level 3`: jumps to the level, the scenery changes like it would on the way there
+    Level(usize),
+    /// `shields 1.0`: sets the ship's shields, 1 is full
+    Shields(f32),
+    /// `god`: the ship cannot be hurt until `god` is typed again
+    God,
+    /// `spawn asteroid 20`: spawns a `fighter`, `asteroid` or `platform` like the spawn table does
+    Spawn(String, usize),
+    /// `timescale 0.25`: how fast the game runs, 1 is normal
+    TimeScale(f32),
+    /// `seed 42`: starts every random stream again from the seed
+    Seed(u64),
+    /// `kill_all`: destroys every opponent like a bomb
+    KillAll,
+}
+
+impl FromStr for ConsoleCommand {
+    type Err = String;
+
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        let mut words = line.split_whitespace();
+        let name = words.next().ok_or("nothing to do")?;
+        let args: Vec<&str> = words.collect();
+        let command = match name {
+            "level" => ConsoleCommand::Level(arg(&args, 0, "level")?),
+            "shields" => ConsoleCommand::Shields(arg(&args, 0, "shields")?),
+            "god" => ConsoleCommand::God,
+            "spawn" => ConsoleCommand::Spawn(arg(&args, 0, "what to spawn")?,
+                                             if args.len() > 1 { arg(&args, 1, "count")? } else { 1 }),
+            "timescale" => ConsoleCommand::TimeScale(arg(&args, 0, "time scale")?),
+            "seed" => ConsoleCommand::Seed(arg(&args, 0, "seed")?),
+            "kill_all" => ConsoleCommand::KillAll,
+            _ => return Err(format!("unknown command {name}")),
+        };
+        Ok(command)
+    }
+}
+
+fn arg<T: FromStr>(args: &[&str], index: usize, what: &str) -> Result<T, String> {
+    let arg = args.get(index).ok_or_else(|| format!("missing {what}"))?;
+    arg.parse().map_err(|_| format!("invalid {what}: {arg}"))
+}
+
+/// The lines waiting to run and what the console answered so far.
+#[derive(Resource, Default)]
+pub struct Console {
+    pub open: bool,
+    /// the line being typed
+    pub input: String,
+    pub log: Vec<String>,
+    /// lines run in the next frame the game is running
+    pub queue: VecDeque<String>,
+    /// the startup script, queued whenever a game starts
+    pub script: Vec<String>,
+}
+
+impl Console {
+    pub fn submit(&mut self, line: impl Into<String>) {
+        self.queue.push_back(line.into());
+    }
+
+    fn print(&mut self, line: String) {
+        self.log.push(line);
+        if self.log.len() > MAX_LOG {
+            self.log.remove(0);
+        }
+    }
+}
+
+/// Commands that change the running game, to try out a level without playing up to it.
+/// The egui console of [`crate::gamedebug::GameDebugPlugin`] types into it, the lines of
+/// `script` are run at the start of every game. Empty lines and `#` comments are skipped.
+#[derive(Default)]
+pub struct ConsolePlugin {
+    pub script: Option<PathBuf>,
+}
+
+impl Plugin for ConsolePlugin {
+    fn build(&self, app: &mut App){
+        let script = self.script.as_ref().map_or_else(Vec::new, |path| match fs::read_to_string(path) {
+            Ok(script) => script.lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                .map(String::from)
+                .collect(),
+            Err(e) => {
+                warn!("cannot read console script {}: {e}", path.display());
+                Vec::new()
+            }
+        });
+        app
+            .insert_resource(Console {
+                script,
+                ..default()
+            })
+            // jumping levels or turning on god mode is no progress to save
+            .init_resource::<SavingDisabled>()
+            .add_systems(OnEnter(InGame), queue_script)
+            .add_systems(OnExit(InGame), reset_time_scale)
+            .add_systems(Update, run_console_commands
+                .before(PlayerInputSet::Read)
+                .run_if(in_state(GameState::Running)));
+    }
+}
+
+fn queue_script(
+    mut console: ResMut<Console>
+){
+    let script = console.script.clone();
+    console.queue.extend(script);
+}
+
+fn reset_time_scale(
+    mut time: ResMut<Time<Virtual>>
+){
+    time.set_relative_speed(1.0);
+}
+
+#[derive(SystemParam)]
+struct ConsoleTargets<'w, 's> {
+    level_entry: LevelEntry<'w, 's>,
+    time: ResMut<'w, Time<Virtual>>,
+    game_rng: ResMut<'w, GameRng>,
+    level_definitions: Res<'w, Assets<LevelDefinition>>,
+    event_spawn: EventWriter<'w, SpawnOpponentEvent>,
+    event_damage: EventWriter<'w, DamageEvent>,
+    query_ship: Query<'w, 's, (&'static mut Ship, &'static mut Health, &'static mut Invulnerability)>,
+    query_opponent: Query<'w, 's, Entity, With<Opponent>>,
+}
+
+impl ConsoleTargets<'_, '_> {
+    /// Runs the command, the answer is shown in the console either way.
+    fn execute(&mut self, command: ConsoleCommand) -> Result<String, String> {
+        match command {
+            ConsoleCommand::Level(value) => {
+                let (mut ship, _, _) = self.query_ship.get_single_mut().map_err(|_| "there is no ship")?;
+                ship.hits = self.level_entry.enter(value).ok_or_else(|| format!("there is no level {value}"))?;
+                Ok(format!("level {value}"))
+            }
+            ConsoleCommand::Shields(fraction) => {
+                let (_, mut health, _) = self.query_ship.get_single_mut().map_err(|_| "there is no ship")?;
+                health.current = health.max * fraction.clamp(0.0, 1.0);
+                Ok(format!("shields {:.0}%", health.fraction() * 100.0))
+            }
+            ConsoleCommand::God => {
+                let (_, _, mut invulnerability) = self.query_ship.get_single_mut().map_err(|_| "there is no ship")?;
+                if invulnerability.remaining.is_infinite() {
+                    invulnerability.remaining = 0.0;
+                    Ok("god mode off".to_string())
+                } else {
+                    invulnerability.remaining = f32::INFINITY;
+                    Ok("god mode on".to_string())
+                }
+            }
+            ConsoleCommand::Spawn(name, count) => {
+                let kind = self.find_spawn(&name).ok_or_else(|| format!("no level spawns a {name}"))?;
+                self.event_spawn.send_batch((0..count).map(|_| SpawnOpponentEvent(kind.clone())));
+                Ok(format!("spawned {count} {name}"))
+            }
+            ConsoleCommand::TimeScale(scale) => {
+                if !scale.is_finite() || scale <= 0.0 {
+                    return Err("the time scale has to be above 0".to_string());
+                }
+                self.time.set_relative_speed(scale);
+                Ok(format!("time scale {scale}"))
+            }
+            ConsoleCommand::Seed(seed) => {
+                *self.game_rng = GameRng::new(seed);
+                Ok(format!("seed {seed}"))
+            }
+            ConsoleCommand::KillAll => {
+                let count = self.query_opponent.iter().count();
+                self.event_damage.send_batch(self.query_opponent.iter().map(|target| DamageEvent {
+                    target,
+                    amount: f32::MAX,
+                    kind: DamageKind::Bomb,
+                }));
+                Ok(format!("destroyed {count} opponents"))
+            }
+        }
+    }
+
+    /// The spawn entry of that kind in the current level, or else in the first level that has one.
+    fn find_spawn(&self, name: &str) -> Option<SpawnKind> {
+        let mut definitions: Vec<&LevelDefinition> = self.level_definitions.iter()
+            .map(|(_, definition)| definition)
+            .collect();
+        let level = self.level_entry.level();
+        definitions.sort_by_key(|definition| (definition.level != level, definition.level));
+        definitions.iter()
+            .flat_map(|definition| definition.spawns.iter())
+            .map(|entry| &entry.kind)
+            .find(|kind| spawn_name(kind) == name)
+            .cloned()
+    }
+}
+
+fn spawn_name(kind: &SpawnKind) -> &'static str {
+    match kind {
+        SpawnKind::Fighter { .. } => "fighter",
+        SpawnKind::Asteroid { .. } => "asteroid",
+        SpawnKind::Platform { .. } => "platform",
+    }
+}
+
+fn run_console_commands(
+    mut console: ResMut<Console>,
+    mut targets: ConsoleTargets,
+){
+    while let Some(line) = console.queue.pop_front() {
+        let answer = line.parse().and_then(|command| targets.execute(command));
+        match &answer {
+            Ok(answer) => info!("console: {line}: {answer}"),
+            Err(e) => warn!("console: {line}: {e}"),
+        }
+        console.print(format!("> {line}"));
+        console.print(answer.unwrap_or_else(|e| e));
+    }
+}
@@ -0,0 +1,107 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::{Collider, Velocity};
+use crate::components::{Armour, Asteroid, Health, Invulnerability, Opponent, Shield, Ship};
+use crate::events::{AsteroidDestroyedEvent, CreateEffectEvent, DamageEvent, DamageKind, OpponentDestroyedEvent};
+use crate::particles::effect_size;
+use crate::resources::{GameStats, Score};
+
+/// Shields of the ship: one enemy laser hit takes a twentieth.
+pub const SHIP_HEALTH: f32 = 20.0;
+/// What an opponent does to the ship when they collide.
+pub const RAM_DAMAGE: f32 = 2.0;
+/// What the ship does to an opponent it rams: enough for a fighter or a big asteroid,
+/// not for a shielded or armoured one.
+pub const SHIP_RAM_DAMAGE: f32 = 4.0;
+/// What scraping along the valley does to the ship.
+pub const TERRAIN_DAMAGE: f32 = 1.0;
+/// How long the ship cannot be hurt after a hit.
+pub const SHIP_INVULNERABILITY: f32 = 0.5;
+
+/// Runs every [`DamageEvent`] of the frame through the target's shield, armour and
+/// invulnerability into its health. Opponents that run out are destroyed and scored.
+pub fn apply_damage(
+    mut commands: Commands,
+    mut event_damage: EventReader<DamageEvent>,
+    mut event_create_effect: EventWriter<CreateEffectEvent>,
+    mut event_destroyed: EventWriter<OpponentDestroyedEvent>,
+    mut event_asteroid_destroyed: EventWriter<AsteroidDestroyedEvent>,
+    mut stats: ResMut<GameStats>,
+    mut score: ResMut<Score>,
+    mut query_ship: Query<&mut Ship>,
+    mut query: Query<(&Transform, &mut Health, Option<&mut Shield>, Option<&Armour>,
+                      Option<&mut Invulnerability>, Option<&Opponent>, Option<&Collider>)>,
+    query_asteroid: Query<(&Asteroid, &Velocity)>,
+){
+    for event in event_damage.read() {
+        let Ok((transform, mut health, shield, armour, invulnerability, opponent, collider)) = query.get_mut(event.target) else {
+            continue;
+        };
+        // both guns can hit in the same frame, only the first one kills
+        if !health.is_alive() {
+            continue;
+        }
+        if let Some(mut invulnerability) = invulnerability {
+            if invulnerability.remaining > 0.0 {
+                continue;
+            }
+            invulnerability.remaining = invulnerability.duration;
+        }
+        let mut amount = event.amount;
+        if let Some(mut shield) = shield {
+            let absorbed = amount.min(shield.current);
+            shield.current -= absorbed;
+            amount -= absorbed;
+        }
+        if let Some(armour) = armour {
+            amount *= 1.0 - armour.reduction.clamp(0.0, 1.0);
+        }
+        health.current -= amount;
+
+        // the ship keeps flying, `test_shields` ends the game
+        if query_ship.contains(event.target) {
+            if event.kind == DamageKind::Projectile {
+                score.break_streak();
+            }
+            continue;
+        }
+        if let Some(opponent) = opponent.filter(|_| !health.is_alive()) {
+            // asteroids crashing into each other are nobody's kill
+            if event.kind != DamageKind::Chip {
+                stats.kills += 1;
+                score.add_kill(opponent.points);
+                if let Ok(mut ship) = query_ship.get_single_mut() {
+                    ship.hits -= 1;
+                }
+            }
+            event_create_effect.send(CreateEffectEvent(transform.translation, effect_size(collider)));
+            // a bomb leaves nothing behind, and there is no loot for a chip
+            if event.kind == DamageKind::Projectile {
+                event_destroyed.send(OpponentDestroyedEvent(transform.translation));
+            }
+            if event.kind != DamageKind::Bomb {
+                if let Ok((asteroid, velocity)) = query_asteroid.get(event.target) {
+                    event_asteroid_destroyed.send(AsteroidDestroyedEvent {
+                        asteroid: *asteroid,
+                        translation: transform.translation,
+                        velocity: velocity.linvel,
+                    });
+                }
+            }
+            commands.entity(event.target).despawn_recursive();
+        }
+    }
+}
+
+/// Recharges shields and counts down invulnerability.
+pub fn tick_defences(
+    time: Res<Time>,
+    mut query_shield: Query<&mut Shield>,
+    mut query_invulnerability: Query<&mut Invulnerability>,
+){
+    for mut shield in query_shield.iter_mut() {
+        shield.current = (shield.current + shield.recharge * time.delta_seconds()).min(shield.max);
+    }
+    for mut invulnerability in query_invulnerability.iter_mut() {
+        invulnerability.remaining = (invulnerability.remaining - time.delta_seconds()).max(0.0);
+    }
+}
@@ -1,5 +1,56 @@
-use bevy::prelude::Event;
+use bevy::prelude::{Entity, Event};
 use bevy::math::Vec3;
+use crate::audio::Sound;
+use crate::components::Asteroid;
+use crate::level::SpawnKind;
 
+/// An explosion, the second field is its size: 1 for a fighter, see [`crate::particles::effect_size`].
 #[derive(Event)]
-pub struct CreateEffectEvent(pub Vec3);
\ No newline at end of file
+pub struct CreateEffectEvent(pub Vec3, pub f32);
+
+/// An opponent was shot down at this position, it may leave a power-up behind.
+#[derive(Event)]
+pub struct OpponentDestroyedEvent(pub Vec3);
+
+/// An asteroid was shot apart, it may break into smaller ones.
+#[derive(Event)]
+pub struct AsteroidDestroyedEvent {
+    pub asteroid: Asteroid,
+    pub translation: Vec3,
+    pub velocity: Vec3,
+}
+
+/// What caused a [`DamageEvent`].
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DamageKind {
+    Projectile,
+    Ram,
+    Terrain,
+    /// opponents destroyed by a bomb leave nothing behind
+    Bomb,
+    /// asteroids crashing into each other, nobody scores for it
+    Chip,
+}
+
+/// `amount` of damage for `target`, before its shield and armour.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct DamageEvent {
+    pub target: Entity,
+    pub amount: f32,
+    pub kind: DamageKind,
+}
+
+/// A sound effect heard from `translation`.
+#[derive(Event)]
+pub struct PlaySoundEvent {
+    pub sound: Sound,
+    pub translation: Vec3,
+}
+
+/// The ship reached the level, sent by [`crate::gameplay::LevelEntry`] for every level entered.
+#[derive(Event)]
+pub struct LevelChangedEvent(pub usize);
+
+/// Spawns an opponent like an entry of the level's spawn table, besides the ones the spawn timer brings.
+#[derive(Event)]
+pub struct SpawnOpponentEvent(pub SpawnKind);
@@ -4,6 +4,26 @@ use bevy::prelude::*;
 pub enum GameState {
     #[default]
     Loading,
+    MainMenu,
     Running,
+    Paused,
     End,
+    Won,
+    Restart,
+}
+
+/// Active while a game is on screen, paused or over. Gameplay entities are scoped
+/// to it, so leaving it despawns all of them.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct InGame;
+
+impl ComputedStates for InGame {
+    type SourceStates = GameState;
+
+    fn compute(sources: GameState) -> Option<Self> {
+        match sources {
+            GameState::Running | GameState::Paused | GameState::End | GameState::Won => Some(InGame),
+            _ => None,
+        }
+    }
 }
\ No newline at end of file
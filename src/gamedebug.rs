@@ -1,36 +1,55 @@
+use std::fmt::Debug;
+use std::path::PathBuf;
+use bevy::input::InputSystem;
 use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
 use bevy_rapier3d::prelude::*;
-use bevy_egui::EguiPlugin;
+use bevy_egui::{egui, EguiContext, EguiContexts, EguiPlugin};
+use crate::actions::{Action, ActionState, ActionSystem, Binding, InputBindings};
+use crate::console::{Console, ConsolePlugin};
+use crate::components::{LaserGun, MainCamera, Opponent, Ship};
 use crate::orbitcamera::{OrbitCameraPlugin, OrbitCamera};
+use crate::resources::{Level, SpawnTimer};
 
-pub struct GameDebugPlugin;
+/// Collider rendering (P), an orbit camera (O), an entity inspector (D) and the
+/// developer console (`), which runs `script` at the start of every game.
+/// Only compiled with the `debug` feature.
+#[derive(Default)]
+pub struct GameDebugPlugin {
+    pub script: Option<PathBuf>,
+}
 
-#[derive(Resource)]
-struct BevyInspector{
-    enabled: bool
+#[derive(Resource, Default)]
+pub struct BevyInspector{
+    pub enabled: bool,
+    /// the entity whose components are shown
+    pub selected: Option<Entity>,
 }
 
 impl Plugin for GameDebugPlugin {
     fn build(&self, app: &mut App){
+        if !app.is_plugin_added::<EguiPlugin>() {
+            app.add_plugins(EguiPlugin);
+        }
         app
-            .insert_resource(BevyInspector{enabled:false})
-            .add_plugins((RapierDebugRenderPlugin::default(), EguiPlugin,
-                          OrbitCameraPlugin))
+            .init_resource::<BevyInspector>()
+            .add_plugins((RapierDebugRenderPlugin{
+                              enabled: false,
+                              ..default()
+                          },
+                          OrbitCameraPlugin,
+                          ConsolePlugin {
+                              script: self.script.clone(),
+                          }))
             .add_systems(Startup, setup_debug)
-            .add_systems( Update, debug);
-            //.add_system(inspector_ui);
+            .add_systems(PreUpdate, keep_keys_from_game.after(InputSystem).before(ActionSystem))
+            .add_systems( Update, (debug, switch_camera, inspector_ui, (toggle_console, console_ui).chain()));
     }
 }
 
-
-
 fn setup_debug(
     mut commands: Commands,
-    mut debug_render_context : ResMut<DebugRenderContext>,
 ) {
-
-    debug_render_context.enabled = false;
-
     commands.spawn(Camera3dBundle{
         camera: Camera{
             is_active:false,
@@ -40,51 +59,181 @@ fn setup_debug(
     })
         .insert(OrbitCamera{
             distance : 28.0,
+            enabled: false,
             ..default()
         })
         .insert(Name::new("OrbitCamera"));
 }
 
 fn debug(
-    keyboard_input:Res<ButtonInput<KeyCode>>,
+    action_state: Res<ActionState>,
     mut debug_render_context : ResMut<DebugRenderContext>,
     mut bevy_inspector:ResMut<BevyInspector>,
-    mut query: Query<&mut Camera>
 )
 {
-    if keyboard_input.just_pressed(KeyCode::KeyO) {
-        for mut camera in query.iter_mut() {
-            camera.is_active = ! camera.is_active
-        }
-    };
-    if keyboard_input.just_pressed(KeyCode::KeyD){
+    if action_state.just_pressed(Action::ToggleInspector){
         bevy_inspector.enabled = !bevy_inspector.enabled;
     };
-    if keyboard_input.just_pressed(KeyCode::KeyP){
+    if action_state.just_pressed(Action::ToggleColliders){
         debug_render_context.enabled = !debug_render_context.enabled;
     }
 }
 
-/*fn inspector_ui(
+/// Switches the view between the [`MainCamera`] and the orbit camera. The orbit camera
+/// starts circling the ship and only takes the mouse while it is the active one.
+pub fn switch_camera(
+    action_state: Res<ActionState>,
+    mut query_main: Query<&mut Camera, (With<MainCamera>, Without<OrbitCamera>)>,
+    mut query_orbit: Query<(&mut Camera, &mut OrbitCamera)>,
+    query_ship: Query<&Transform, With<Ship>>,
+) {
+    if !action_state.just_pressed(Action::ToggleDebugCamera) {
+        return;
+    }
+    let Ok((mut orbit_camera, mut orbit)) = query_orbit.get_single_mut() else {
+        return;
+    };
+    let orbiting = !orbit_camera.is_active;
+    orbit_camera.is_active = orbiting;
+    orbit.enabled = orbiting;
+    if orbiting {
+        if let Ok(ship) = query_ship.get_single() {
+            orbit.center = ship.translation;
+        }
+    }
+    for mut camera in query_main.iter_mut() {
+        camera.is_active = !orbiting;
+    }
+}
+
+/// Lists every entity, shows the gameplay components of the selected one and the level resources.
+fn inspector_ui(
     world: &mut World
 ) {
-    let egui_context = world.resource_mut::<bevy_egui::EguiContext>().ctx_mut().clone();
+    let inspector = world.resource::<BevyInspector>();
+    if !inspector.enabled {
+        return;
+    }
+    let mut open = true;
+    let mut selected = inspector.selected.filter(|entity| world.get_entity(*entity).is_some());
+    let Ok(mut egui_context) = world.query_filtered::<&mut EguiContext, With<PrimaryWindow>>().get_single_mut(world) else {
+        return;
+    };
+    let context = egui_context.get_mut().clone();
 
-    let bevy_inspector = world.get_resource::<BevyInspector>().unwrap();
-    if bevy_inspector.enabled {
-        Window::new("UI").show(&egui_context, |ui| {
-        ScrollArea::vertical().show(ui, |ui| {
-            // equivalent to `WorldInspectorPlugin`
-            // bevy_inspector_egui::bevy_inspector::ui_for_world(world, ui);
+    let mut entities: Vec<(Entity, String)> = world.query::<(Entity, Option<&Name>)>().iter(world)
+        .map(|(entity, name)| (entity, name.map_or_else(|| format!("{entity}"), |name| format!("{name} {entity}"))))
+        .collect();
+    entities.sort_by_key(|(entity, _)| *entity);
+    let values: Vec<(&str, String)> = selected.map_or_else(Vec::new, |entity| [
+        ("Ship", debug_value::<Ship>(world, entity)),
+        ("Opponent", debug_value::<Opponent>(world, entity)),
+        ("LaserGun", debug_value::<LaserGun>(world, entity)),
+    ].into_iter().filter_map(|(name, value)| Some((name, value?))).collect());
+    let components: Vec<&str> = selected.map_or_else(Vec::new, |entity| world.inspect_entity(entity).iter()
+        .map(|info| info.name())
+        .collect());
 
-            CollapsingHeader::new("Materials").show(ui, |ui| {
-                bevy_inspector_egui::bevy_inspector::ui_for_assets::<StandardMaterial>(world, ui);
+    egui::Window::new("Inspector").open(&mut open).default_width(360.0).show(&context, |ui| {
+        egui::CollapsingHeader::new("Resources").default_open(true).show(ui, |ui| {
+            ui.monospace(format!("{:#?}", world.get_resource::<Level>()));
+            ui.monospace(format!("{:#?}", world.get_resource::<SpawnTimer>()));
+        });
+        egui::CollapsingHeader::new(format!("Entities ({})", entities.len())).default_open(true).show(ui, |ui| {
+            egui::ScrollArea::vertical().max_height(240.0).show(ui, |ui| {
+                for (entity, label) in entities.iter() {
+                    if ui.selectable_label(selected == Some(*entity), label).clicked() {
+                        selected = Some(*entity);
+                    }
+                }
             });
-
-            ui.heading("Entities");
-            bevy_inspector_egui::bevy_inspector::ui_for_world_entities(world, ui);
         });
+        if let Some(entity) = selected {
+            ui.separator();
+            ui.heading(format!("{entity}"));
+            for (name, value) in values.iter() {
+                egui::CollapsingHeader::new(*name).default_open(true).show(ui, |ui| {
+                    ui.monospace(value);
+                });
+            }
+            egui::CollapsingHeader::new("Components").show(ui, |ui| {
+                for component in components.iter() {
+                    ui.label(*component);
+                }
+            });
+        }
     });
+
+    let mut inspector = world.resource_mut::<BevyInspector>();
+    if inspector.enabled != open || inspector.selected != selected {
+        inspector.enabled = open;
+        inspector.selected = selected;
+    }
+}
+
+fn toggle_console(
+    action_state: Res<ActionState>,
+    mut console: ResMut<Console>,
+){
+    if action_state.just_pressed(Action::ToggleConsole) {
+        console.open = !console.open;
+    }
+}
+
+/// Typing into the console must not fly the ship, only the keys that close it get through.
+fn keep_keys_from_game(
+    mut egui_contexts: EguiContexts,
+    console: Res<Console>,
+    bindings: Res<InputBindings>,
+    mut keys: ResMut<ButtonInput<KeyCode>>,
+){
+    if !console.open || !egui_contexts.ctx_mut().wants_keyboard_input() {
+        return;
     }
+    let toggles = bindings.bindings(Action::ToggleConsole);
+    let typed: Vec<KeyCode> = keys.get_pressed().chain(keys.get_just_released()).copied().collect();
+    for key in typed {
+        if !toggles.contains(&Binding::Key(key)) {
+            keys.reset(key);
+        }
+    }
+}
+
+/// Drops down from the top of the window, enter runs the line.
+fn console_ui(
+    mut egui_contexts: EguiContexts,
+    mut console: ResMut<Console>,
+){
+    if !console.open {
+        return;
+    }
+    let console = &mut *console;
+    // the key that opened the console
+    console.input.retain(|c| c != '`');
+    egui::Window::new("Console")
+        .title_bar(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_TOP, [0.0, 0.0])
+        .fixed_size([700.0, 240.0])
+        .show(egui_contexts.ctx_mut(), |ui| {
+            egui::ScrollArea::vertical().max_height(200.0).stick_to_bottom(true).show(ui, |ui| {
+                for line in console.log.iter() {
+                    ui.monospace(line);
+                }
+            });
+            let input = ui.add(egui::TextEdit::singleline(&mut console.input)
+                .font(egui::TextStyle::Monospace)
+                .desired_width(f32::INFINITY));
+            if input.lost_focus() && ui.input(|input| input.key_pressed(egui::Key::Enter)) {
+                let line = std::mem::take(&mut console.input);
+                if !line.trim().is_empty() {
+                    console.submit(line.trim());
+                }
+            }
+            input.request_focus();
+        });
+}
+
+fn debug_value<T: Component + Debug>(world: &World, entity: Entity) -> Option<String> {
+    world.get::<T>(entity).map(|value| format!("{value:#?}"))
 }
-*/
\ No newline at end of file
@@ -0,0 +1,885 @@
+use std::time::Duration;
+use std::f32::consts::PI;
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use bevy::color::palettes::basic::*;
+use bevy::color::palettes::css::*;
+use bevy_rapier3d::prelude::*;
+use rand::Rng;
+use rand_chacha::ChaCha8Rng;
+use crate::actions::{Action, ActionState, ActionsPlugin};
+use crate::skybox::{RotateSkyboxEvent, Skybox};
+use crate::components::*;
+use crate::audio::Sound;
+use crate::events::{AsteroidDestroyedEvent, CreateEffectEvent, DamageEvent, LevelChangedEvent, OpponentDestroyedEvent,
+                    PlaySoundEvent, SpawnOpponentEvent};
+use crate::game_state::{GameState, InGame};
+use crate::resources::{ActivePowerUps, BossFight, GameAssets, GameStats, Level, PlayerInput, Score, SpawnTimer,
+                       StartLevel, UnlockedWeapons};
+use crate::asteroid::{random_spin, spawn_asteroid, split_asteroids};
+use crate::boss::{boss_attack, destroy_boss_parts, handle_boss_hits, move_boss, spawn_boss};
+use crate::canyon::{Canyon, Segment};
+use crate::collision::handle_collisions;
+use crate::damage::{apply_damage, tick_defences, RAM_DAMAGE, SHIP_HEALTH, SHIP_INVULNERABILITY, TERRAIN_DAMAGE};
+use crate::powerup::{attract_power_ups, drop_power_ups, update_power_ups, PLAYER_GUNS};
+use crate::weapon::{fire_weapon, steer_missiles, Weapon, CHARGE_TIME};
+use crate::level::{sample_range, LevelDefinition, SpawnKind};
+use crate::rng::GameRng;
+use crate::particles::{create_effect, emit_particles, spark_shield_hits, update_particles, ParticleEmitter};
+use crate::pool::{release_pooled, setup_shared_handles, EntityPool, PoolCommands, PoolKind, SharedHandles};
+
+/// The gameplay itself: ship, opponents, lasers, effects and level changes.
+/// Shared by the windowed game and the headless [`crate::sim::GameSimPlugin`].
+pub struct GameplayPlugin;
+
+/// [`PlayerInput`] is filled in `Read` and may be replaced in `Override` (e.g. by a replay)
+/// before the ship consumes it.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum PlayerInputSet {
+    Read,
+    Override,
+}
+
+/// Where a new game is set up in `OnEnter(InGame)`, systems that change the new game
+/// (e.g. continuing a saved one) run after it.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SetupSet;
+
+impl Plugin for GameplayPlugin {
+    fn build(&self, app: &mut App){
+        app
+            .insert_resource(Level{value:1})
+            .init_resource::<StartLevel>()
+            .init_resource::<UnlockedWeapons>()
+            .insert_resource(SpawnTimer(Timer::from_seconds(2.0,TimerMode::Repeating)))
+            .init_resource::<GameRng>()
+            .init_resource::<PlayerInput>()
+            .init_resource::<GameStats>()
+            .init_resource::<Score>()
+            .init_resource::<BossFight>()
+            .init_resource::<Canyon>()
+            .init_resource::<ActivePowerUps>()
+            .init_resource::<EntityPool>()
+            .add_plugins(ActionsPlugin)
+            .add_event::<CreateEffectEvent>()
+            .add_event::<OpponentDestroyedEvent>()
+            .add_event::<DamageEvent>()
+            .add_event::<AsteroidDestroyedEvent>()
+            .add_event::<PlaySoundEvent>()
+            .add_event::<SpawnOpponentEvent>()
+            .add_event::<LevelChangedEvent>()
+            .add_event::<RotateSkyboxEvent>()
+            .add_computed_state::<InGame>()
+            .enable_state_scoped_entities::<InGame>()
+            .add_systems(Startup, (log_seed, setup_shared_handles))
+            .add_systems(OnEnter(InGame), (setup, enter_start_level).chain().in_set(SetupSet))
+            .add_systems(OnExit(InGame), (reset_game, release_pooled))
+            .add_systems(OnEnter(GameState::Restart), restart)
+            .add_systems(OnEnter(GameState::Paused), pause_physics)
+            .add_systems(OnExit(GameState::Paused), resume_physics)
+            .add_systems(Update, toggle_pause.run_if(in_state(GameState::Running).or_else(in_state(GameState::Paused))))
+            .configure_sets(Update, (PlayerInputSet::Read, PlayerInputSet::Override).chain()
+                .run_if(in_state(GameState::Running)))
+            .add_systems(Update, read_player_input.in_set(PlayerInputSet::Read))
+            // chained so a run (and its replay) always updates in the same order
+            .add_systems(Update, ((move_ship, steer_opponents, move_boss, attract_power_ups, steer_missiles).chain(),
+                                  (laser_player, update_power_ups, laser_opponent, spawn_laser, boss_attack).chain(),
+                                  (tick_defences, handle_collisions, handle_boss_hits, spark_shield_hits, apply_damage,
+                                   destroy_boss_parts, split_asteroids, drop_power_ups, create_effect, emit_particles, update_particles).chain(),
+                                  (change_level, unlock_weapons, spawn_boss, test_shields, spawn_opponent, despawn_all, count_time).chain())
+                .chain()
+                .after(PlayerInputSet::Override)
+                .run_if(in_state(GameState::Running)));
+    }
+}
+
+fn log_seed(game_rng: Res<GameRng>) {
+    info!("seed {}", game_rng.seed);
+}
+
+const SHIP_POSTION: Vec3 = Vec3::new(0.0, 0.0, -25.0);
+
+fn setup(
+    mut commands: Commands,
+    game_assets: Res<GameAssets>,
+    level_definitions: Res<Assets<LevelDefinition>>,
+    level: Res<Level>,
+    mut spawn_timer: ResMut<SpawnTimer>,
+) {
+    let definition = game_assets.level(&level_definitions, level.value);
+    if let Some(definition) = definition {
+        spawn_timer.0.set_duration(Duration::from_secs_f32(definition.spawn_interval));
+    }
+
+    //light
+    commands.spawn(DirectionalLightBundle {
+        directional_light: DirectionalLight {
+            shadows_enabled: true,
+            ..default()
+        },
+        transform: Transform {
+            translation: Vec3::new(0.0, 11.6, -15.1),
+            rotation: Quat::from_rotation_x(-std::f32::consts::FRAC_PI_4),
+            ..default()
+        },
+        ..default()
+    })
+        .insert(StateScoped(InGame));
+
+    // ambient light
+    commands.insert_resource(AmbientLight {
+        color: Color::WHITE,
+        brightness: 0.2,
+    });
+
+    //ship
+
+    commands.spawn(SceneBundle {
+        scene: game_assets.fighter_scene.clone(),
+        transform:Transform {
+            translation: SHIP_POSTION,
+            scale: Vec3::new(1.0,1.0,1.0),
+            //rotation: Quat::from_rotation_y(std::f32::consts::FRAC_PI_2),
+            ..default()
+        },
+        ..Default::default()
+    })
+    .insert(RigidBody::KinematicVelocityBased)
+    .insert(Velocity {
+        linvel: Vec3::new(0.0, 0.0, 0.0),
+        ..default()
+    })
+    .insert(Collider::cuboid(3.0,
+                             1.0,
+                             3.0))
+    .insert(ActiveEvents::COLLISION_EVENTS)
+    .insert(GravityScale(0.0))
+    .insert(Name::new("Ship"))
+    .insert(StateScoped(InGame))
+    .insert(Health::new(SHIP_HEALTH))
+    .insert(Invulnerability {
+        duration: SHIP_INVULNERABILITY,
+        remaining: 0.0,
+    })
+    .insert(ParticleEmitter::exhaust())
+    .insert(Ship{
+        hits: definition.map_or(CHANGE_LEVEL_HITS, |d| d.hits_to_advance),
+        win_or_lost: WinOrLostState::Neutral
+    })
+    .insert(LaserGun{
+        weapon: Weapon::Laser,
+        positions: PLAYER_GUNS.to_vec(),
+        player: true,
+        color: Color::Srgba(LIME),
+        fire: false,
+        std_cooldown: Weapon::Laser.spec().cooldown,
+        cooldown:0.0,
+        aim: None,
+        charge: 0.0,
+    });
+
+    //planet
+
+    commands.spawn(SceneBundle {
+        scene: game_assets.planet_scene.clone(),
+        transform:Transform {
+            translation: Vec3::new(-80.0,0.0,-320.0),
+            scale: Vec3::new(16.0,16.0,16.0),
+            //rotation: Quat::from_rotation_y(std::f32::consts::FRAC_PI_2),
+            ..default()
+        },
+        ..Default::default()
+    })
+        .insert(Planet{})
+        .insert(Name::new("Planet"))
+        .insert(StateScoped(InGame));
+
+    //planet down
+
+    commands.spawn(SceneBundle {
+        scene: game_assets.planet_down_scene.clone(),
+        transform:Transform {
+            translation: Vec3::new(0.0,-180.0,-146.0),
+            scale: Vec3::new(128.0,128.0,128.0),
+            //rotation: Quat::from_rotation_y(std::f32::consts::FRAC_PI_2),
+            ..default()
+        },
+        ..Default::default()
+    })
+        .insert(Planet{})
+        .insert(Name::new("Planet down"))
+        .insert(StateScoped(InGame));
+
+}
+
+
+const MAXSPEED:f32 = 30.0;
+const ACCELERATION:f32 = 0.75;
+
+fn read_player_input(
+    action_state: Res<ActionState>,
+    mut player_input: ResMut<PlayerInput>
+){
+    player_input.horizontal = action_state.value(Action::MoveX);
+    player_input.vertical = action_state.value(Action::MoveY);
+    player_input.fire = action_state.pressed(Action::Fire);
+    player_input.next_weapon = action_state.just_pressed(Action::NextWeapon);
+}
+
+fn move_ship(
+    player_input: Res<PlayerInput>,
+    mut query: Query<(&mut Velocity, &mut Transform),With<Ship>>
+){
+    let (mut velo, mut transform) = query.single_mut();
+
+    velo.linvel.x  = velo.linvel.x.lerp(player_input.horizontal * MAXSPEED, ACCELERATION);
+    velo.linvel.y = velo.linvel.y.lerp(player_input.vertical * MAXSPEED, ACCELERATION);
+
+    transform.rotation = Quat::from_euler( EulerRot::YXZ,
+                                           (-velo.linvel.y / 2.0).to_radians(), //1.5*std::f32::consts::PI, //
+                                           -(velo.linvel.y / 2.0).to_radians(),
+                                           (-velo.linvel.x).to_radians());
+
+    if transform.translation.x < -15.0
+    {
+        velo.linvel.x = 0.0;
+        transform.translation.x = -15.0
+    }
+    if transform.translation.x > 15.0
+    {
+        velo.linvel.x = 0.0;
+        transform.translation.x = 15.0
+    }
+    if transform.translation.y < -8.0
+    {
+        velo.linvel.y = 0.0;
+        transform.translation.y = -8.0
+    }
+    if transform.translation.y > 10.0
+    {
+        velo.linvel.y = 0.0;
+        transform.translation.y = 10.0
+    }
+}
+
+const SPAWN_POS:Vec3 = Vec3::new(0.0,0.0,-300.0);
+/// Seconds an opponent's shield takes to recharge from empty.
+const SHIELD_RECHARGE_TIME:f32 = 4.0;
+const TOWER_HEIGHT:f32 = 16.0;
+const PLATFORM_START:Vec3 = Vec3::new(0.0, 0.0, -240.0);
+
+fn spawn_opponent(
+    mut commands: Commands,
+    time:Res<Time>,
+    mut spawn_timer: ResMut<SpawnTimer>,
+    level: Res<Level>,
+    level_definitions: Res<Assets<LevelDefinition>>,
+    mut pool: ResMut<EntityPool>,
+    mut handles: ResMut<SharedHandles>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    game_assets: Res<GameAssets>,
+    mut game_rng: ResMut<GameRng>,
+    mut canyon: ResMut<Canyon>,
+    boss_fight: Res<BossFight>,
+    mut event_spawn: EventReader<SpawnOpponentEvent>,
+){
+    let GameRng { spawn: rng, ai, .. } = &mut *game_rng;
+    // asked for outside the spawn table, e.g. by the debug console, they come even during a boss fight
+    let mut kinds: Vec<SpawnKind> = event_spawn.read().map(|event| event.0.clone()).collect();
+    // nothing else comes while the boss is there
+    if *boss_fight != BossFight::Fighting && spawn_timer.0.tick(time.delta()).just_finished() {
+        if let Some(entry) = game_assets
+            .level(&level_definitions, level.value)
+            .and_then(|definition| definition.choose_spawn(rng)) {
+            kinds.push(entry.kind.clone());
+        }
+    }
+    for kind in kinds.iter() {
+        spawn_kind(&mut commands, kind, &spawn_timer.0, rng, ai, &mut pool, &mut handles, &mut materials,
+                   &game_assets, &mut canyon);
+    }
+}
+
+/// Spawns what a spawn entry describes, `spawn_timer` lines the valley sections up.
+fn spawn_kind(
+    commands: &mut Commands,
+    kind: &SpawnKind,
+    spawn_timer: &Timer,
+    rng: &mut ChaCha8Rng,
+    ai: &mut ChaCha8Rng,
+    pool: &mut EntityPool,
+    handles: &mut SharedHandles,
+    materials: &mut Assets<StandardMaterial>,
+    game_assets: &GameAssets,
+    canyon: &mut Canyon,
+){
+    match *kind {
+        SpawnKind::Fighter { speed, cooldown, hits, points, flight, lead_shots, weapon, armour, shield } => {
+            let leader = SPAWN_POS + Vec3::new(rng.gen_range(-15.0..15.0),
+                                               rng.gen_range(-10.0..10.0),
+                                               0.0);
+            let speed = sample_range(rng, speed);
+            let strafe_direction = if ai.gen_bool(0.5) { 1.0 } else { -1.0 };
+
+            for offset in formation_offsets(flight) {
+                let translation = leader + offset;
+                let linvel = match flight {
+                    Flight::Strafe { speed: strafe_speed } => Vec3::new(strafe_direction * strafe_speed, 0.0, speed),
+                    _ => Vec3::new(0.0, 0.0, speed),
+                };
+                let fighter = commands.spawn(SceneBundle {
+                    scene: game_assets.opponent_1_scene.clone(),
+                    transform: Transform {
+                        translation,
+                        ..default()
+                    },
+                    ..Default::default()
+                })
+                    .insert(RigidBody::Dynamic)
+                    .insert(Velocity {
+                        linvel,
+                        ..default()
+                    })
+                    .insert(Collider::cuboid(3.0, 3.0, 3.0))
+                    .insert(ActiveEvents::COLLISION_EVENTS)
+                    .insert(GravityScale(0.0))
+                    .insert(Despawnable {
+                        min: -1000.0,
+                        max: 0.0
+                    })
+                    .insert(LaserGun {
+                        weapon,
+                        positions: vec!(
+                            Vec3::new(0.0, 0.0, 5.0)
+                        ),
+                        player: false,
+                        color: Color::Srgba(MIDNIGHT_BLUE),
+                        fire: false,
+                        cooldown: 0.0,
+                        std_cooldown: sample_range(ai, cooldown).max(weapon.spec().cooldown),
+                        aim: None,
+                        charge: 0.0,
+                    })
+                    .insert(OpponentBehaviour {
+                        flight,
+                        lead_shots,
+                        age: 0.0,
+                        origin: translation,
+                    })
+                    .insert(Name::new("Opponent"))
+                    .insert(Opponent {
+                        points: points * hits.max(1) as u32,
+                    })
+                    .insert(Health::new(hits as f32))
+                    .insert(Damage {
+                        amount: RAM_DAMAGE,
+                    })
+                    .insert(StateScoped(InGame))
+                    .id();
+                if armour > 0.0 {
+                    commands.entity(fighter).insert(Armour {
+                        reduction: armour,
+                    });
+                }
+                if shield > 0.0 {
+                    commands.entity(fighter).insert(Shield {
+                        current: shield,
+                        max: shield,
+                        recharge: shield / SHIELD_RECHARGE_TIME,
+                    });
+                }
+            }
+        },
+        SpawnKind::Asteroid { speed, scale, hits, points, chip_damage } => {
+            let factor = sample_range(rng, scale);
+            let asteroid = Asteroid {
+                scale: factor,
+                toughness: hits.max(1) as f32 / scale.1.max(scale.0),
+                points,
+                min_scale: scale.0,
+                chip_damage,
+            };
+            let translation = SPAWN_POS + Vec3::new(rng.gen_range(-15.0..15.0),
+                                                    rng.gen_range(-10.0..10.0),
+                                                    0.0);
+            let velocity = Velocity {
+                linvel: Vec3::new(0.0, 0.0, sample_range(rng, speed)),
+                angvel: random_spin(rng),
+            };
+            spawn_asteroid(commands, game_assets, asteroid, translation, velocity);
+        },
+        SpawnKind::Platform { speed, towers } => {
+            // the segment has moved on since the timer finished, so it lines up with the last one
+            let length = speed * spawn_timer.duration().as_secs_f32();
+            let start = PLATFORM_START + Vec3::Z * speed * spawn_timer.elapsed_secs();
+            let (near, far) = canyon.next_segment(rng);
+            let segment = Segment::new(&near, &far, length);
+
+            let rnd_texture = rng.gen_range(1..=8);
+            let texture_handle = match rnd_texture {
+                 1 => game_assets.tile_1_texture.clone(),
+                 2 => game_assets.tile_2_texture.clone(),
+                 3 => game_assets.tile_3_texture.clone(),
+                 4 => game_assets.tile_4_texture.clone(),
+                 5 => game_assets.tile_5_texture.clone(),
+                 6 => game_assets.tile_6_texture.clone(),
+                 7 => game_assets.tile_7_texture.clone(),
+                _ => game_assets.tile_8_texture.clone()
+            };
+
+            // the mesh of a recycled segment is reshaped, not made again
+            let segment_entity = pool.acquire(commands, PoolKind::Segment);
+            commands.set_mesh(segment_entity, segment.mesh());
+            commands
+                .entity(segment_entity)
+                .insert((handles.textured_material(materials, &texture_handle),
+                         TransformBundle::from_transform(Transform::from_translation(start)),
+                         VisibilityBundle::default()))
+                .insert(RigidBody::KinematicVelocityBased)
+                .insert(Velocity {
+                    linvel: Vec3::new(0.0, 0.0, speed),
+                    ..default()
+                })
+                .insert(segment.collider())
+                .insert(ActiveEvents::COLLISION_EVENTS)
+                // the ship and the lasers are kinematic as well
+                .insert(ActiveCollisionTypes::default() | ActiveCollisionTypes::KINEMATIC_KINEMATIC)
+                .insert(scenery_collision_groups())
+                .insert(GravityScale(0.0))
+                .insert(Despawnable {
+                    min: -1000.0,
+                    max: 0.0
+                })
+                .insert(Name::new("Terrain"))
+                .insert(Terrain)
+                .insert(Damage {
+                    amount: TERRAIN_DAMAGE,
+                });
+
+            if let Some(towers) = towers.filter(|towers| rng.gen_bool(towers.chance)) {
+                // standing on the valley floor, tall enough to reach into the ship's flight space
+                let middle = near.lerp(&far, 0.5);
+                let x = rng.gen_range(-0.6..0.6) * middle.half_width;
+                let floor = Vec3::new(x, middle.floor_at(x), start.z);
+                commands.spawn(SceneBundle {
+                    scene: game_assets.tower_scene.clone(),
+                    transform: Transform {
+                        translation: floor,
+                        scale: Vec3::splat(TOWER_HEIGHT / 2.0),
+                        ..default()
+                    },
+                    ..default()
+                })
+                    .insert(RigidBody::KinematicVelocityBased)
+                    .insert(Velocity {
+                        linvel: Vec3::new(0.0, 0.0, speed),
+                        ..default()
+                    })
+                    .insert(Collider::compound(vec![(Vec3::Y, Quat::IDENTITY, Collider::cuboid(0.25, 1.0, 0.25))]))
+                    .insert(ActiveEvents::COLLISION_EVENTS)
+                    .insert(ActiveCollisionTypes::default() | ActiveCollisionTypes::KINEMATIC_KINEMATIC)
+                    .insert(scenery_collision_groups())
+                    .insert(Despawnable {
+                        min: -1000.0,
+                        max: 0.0
+                    })
+                    .insert(LaserGun {
+                        weapon: towers.weapon,
+                        positions: vec!(
+                            Vec3::new(0.0, TOWER_HEIGHT, 0.0)
+                        ),
+                        player: false,
+                        color: Color::Srgba(ORANGE_RED),
+                        fire: false,
+                        cooldown: 0.0,
+                        std_cooldown: sample_range(ai, towers.cooldown).max(towers.weapon.spec().cooldown),
+                        aim: None,
+                        charge: 0.0,
+                    })
+                    // towers keep their place but aim at the ship
+                    .insert(OpponentBehaviour {
+                        flight: Flight::Straight,
+                        lead_shots: true,
+                        age: 0.0,
+                        origin: floor,
+                    })
+                    .insert(Name::new("Tower"))
+                    .insert(Tower)
+                    .insert(Opponent {
+                        points: towers.points * towers.hits.max(1) as u32,
+                    })
+                    .insert(Health::new(towers.hits as f32))
+                    .insert(Damage {
+                        amount: RAM_DAMAGE,
+                    })
+                    .insert(StateScoped(InGame));
+            }
+        }
+    }
+}
+
+/// Canyon segments and towers touch each other all the time, so they skip each other.
+fn scenery_collision_groups() -> CollisionGroups {
+    CollisionGroups::new(Group::GROUP_2, Group::ALL ^ Group::GROUP_2)
+}
+
+fn despawn_all(
+    mut commands: Commands,
+    mut query: Query<(Entity,&Transform, &Despawnable)>,
+) {
+    for (e, transform, limits) in query.iter_mut(){
+        if transform.translation.z >= limits.max || transform.translation.z <= limits.min {
+            commands.release(e);
+        }
+    }
+}
+
+fn laser_player(
+    player_input: Res<PlayerInput>,
+    unlocked_weapons: Res<UnlockedWeapons>,
+    mut query: Query<&mut LaserGun,With<Ship>>
+){
+    let mut laser_gun = query.single_mut();
+    laser_gun.fire = player_input.fire;
+    if player_input.next_weapon {
+        laser_gun.weapon = unlocked_weapons.next(laser_gun.weapon);
+        laser_gun.charge = 0.0;
+        info!("weapon {}", laser_gun.weapon.name());
+    }
+}
+
+fn laser_opponent(
+    query_ship: Query<(&Transform, &Velocity), With<Ship>>,
+    mut query: Query<(&Transform, &mut LaserGun, Option<&OpponentBehaviour>), With<Opponent>>,
+){
+    let ship = query_ship.get_single().ok();
+    for (transfrom, mut laser_gun, behaviour) in query.iter_mut() {
+        laser_gun.fire = transfrom.translation.z.abs() < 200.0;
+        laser_gun.aim = match (ship, behaviour) {
+            (Some((ship_transform, ship_velocity)), Some(behaviour)) if behaviour.lead_shots =>
+                lead_target(transfrom.translation, ship_transform.translation, ship_velocity.linvel,
+                            laser_gun.weapon.spec().speed)
+                    .map(|target| (target - transfrom.translation).normalize()),
+            _ => None,
+        };
+    }
+}
+
+/// Where a shot fired from `from` meets a ship that keeps its velocity,
+/// or `None` if the ship is behind the shooter.
+fn lead_target(from: Vec3, ship: Vec3, ship_velocity: Vec3, shot_speed: f32) -> Option<Vec3> {
+    if ship.z <= from.z {
+        return None;
+    }
+    // two rounds are close enough for a ship much slower than the laser
+    let mut target = ship;
+    for _ in 0..2 {
+        let flight_time = from.distance(target) / shot_speed;
+        target = ship + ship_velocity * flight_time;
+    }
+    Some(target)
+}
+
+/// Offsets of the fighters in one spawn, the leader first.
+fn formation_offsets(flight: Flight) -> Vec<Vec3> {
+    match flight {
+        Flight::Formation { count, spacing } => (0..count.max(1))
+            .map(|i| {
+                let rank = i.div_ceil(2);
+                let side = if i % 2 == 0 { 1.0 } else { -1.0 };
+                Vec3::new(side * rank as f32 * spacing, 0.0, -(rank as f32) * spacing)
+            })
+            .collect(),
+        _ => vec![Vec3::ZERO],
+    }
+}
+
+fn steer_opponents(
+    time: Res<Time>,
+    query_ship: Query<&Transform, (With<Ship>, Without<Opponent>)>,
+    mut query: Query<(&Transform, &mut Velocity, &mut OpponentBehaviour), With<Opponent>>,
+){
+    let ship = query_ship.get_single().ok();
+    let dt = time.delta_seconds();
+    for (transform, mut velocity, mut behaviour) in query.iter_mut() {
+        behaviour.age += dt;
+        match behaviour.flight {
+            Flight::Straight | Flight::Formation { .. } => {}
+            Flight::Strafe { speed } => {
+                if transform.translation.x > 15.0 {
+                    velocity.linvel.x = -speed;
+                } else if transform.translation.x < -15.0 {
+                    velocity.linvel.x = speed;
+                }
+            }
+            Flight::Weave { amplitude, frequency } => {
+                // the derivative of `origin.x + amplitude * sin(age * frequency * 2π)`
+                let omega = frequency * 2.0 * PI;
+                velocity.linvel.x = amplitude * omega * (behaviour.age * omega).cos();
+            }
+            Flight::Kamikaze { turn_rate } => {
+                // once past the ship it just flies on
+                if let Some(ship) = ship.filter(|ship| ship.translation.z > transform.translation.z) {
+                    let speed = velocity.linvel.length();
+                    let wanted = (ship.translation - transform.translation).normalize_or_zero() * speed;
+                    velocity.linvel = velocity.linvel.lerp(wanted, (turn_rate * dt).min(1.0));
+                }
+            }
+        }
+    }
+}
+
+fn spawn_laser(
+    mut commands: Commands,
+    time:Res<Time>,
+    mut pool: ResMut<EntityPool>,
+    mut handles: ResMut<SharedHandles>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut stats: ResMut<GameStats>,
+    mut event_sound: EventWriter<PlaySoundEvent>,
+    mut query: Query<(&Transform,&mut LaserGun)>
+)
+{
+    for (transform, mut laser_gun) in query.iter_mut() {
+        let trigger = if laser_gun.weapon == Weapon::ChargedBeam {
+            if laser_gun.fire && laser_gun.cooldown <= 0.0 {
+                laser_gun.charge = (laser_gun.charge + time.delta_seconds()).min(CHARGE_TIME);
+            }
+            // the player lets go to fire, opponents fire as soon as the beam is charged
+            let released = !laser_gun.fire || (!laser_gun.player && laser_gun.charge >= CHARGE_TIME);
+            laser_gun.charge > 0.0 && released
+        } else {
+            laser_gun.fire && laser_gun.cooldown <= 0.0
+        };
+        if trigger {
+            laser_gun.cooldown = laser_gun.std_cooldown;
+            event_sound.send(PlaySoundEvent {
+                sound: Sound::Laser,
+                translation: transform.translation,
+            });
+            let charge = laser_gun.charge / CHARGE_TIME;
+            laser_gun.charge = 0.0;
+            for gun in &laser_gun.positions {
+                let direction = match laser_gun.aim {
+                    Some(aim) => aim,
+                    None if laser_gun.player => *transform.forward(),
+                    None => *transform.back(),
+                };
+                let shots = fire_weapon(&mut commands, &mut pool, &mut handles, &mut materials, laser_gun.weapon, charge,
+                                        transform.translation + *gun, direction,
+                                        laser_gun.color, laser_gun.player);
+                if laser_gun.player {
+                    stats.shots += shots;
+                }
+            }
+        } else if laser_gun.fire {
+            laser_gun.cooldown -= time.delta_seconds();
+        }
+    }
+}
+
+const CHANGE_LEVEL_HITS:i32 = 40;
+
+/// What changes when the ship reaches a level: the level itself, its spawn interval, the
+/// skybox, the planets and the opponents. Used when a level is finished, when a game starts
+/// at a later level and by everything else that jumps between levels.
+#[derive(SystemParam)]
+pub struct LevelEntry<'w, 's> {
+    commands: Commands<'w, 's>,
+    level: ResMut<'w, Level>,
+    spawn_timer: ResMut<'w, SpawnTimer>,
+    boss_fight: ResMut<'w, BossFight>,
+    game_assets: Res<'w, GameAssets>,
+    level_definitions: Res<'w, Assets<LevelDefinition>>,
+    event_rotate_skybox: EventWriter<'w, RotateSkyboxEvent>,
+    event_level_changed: EventWriter<'w, LevelChangedEvent>,
+    query_planet: Query<'w, 's, Entity, With<Planet>>,
+    query_opponent: Query<'w, 's, Entity, With<Opponent>>,
+    query_boss: Query<'w, 's, Entity, With<Boss>>,
+}
+
+impl LevelEntry<'_, '_> {
+    pub fn level(&self) -> usize {
+        self.level.value
+    }
+
+    /// Enters every level after the current one up to `target`, as if each had been finished
+    /// in turn, or only `target` when it is not ahead. A boss still flying leaves with its level.
+    /// Returns the hits the ship needs for the next level, `None` if there is no level `target`.
+    pub fn enter(&mut self, target: usize) -> Option<i32> {
+        self.game_assets.level(&self.level_definitions, target)?;
+        for e in self.query_boss.iter() {
+            self.commands.entity(e).despawn_recursive();
+        }
+        *self.boss_fight = BossFight::None;
+
+        let first = if target > self.level.value { self.level.value + 1 } else { target };
+        let mut hits = None;
+        for value in first..=target {
+            let Some(definition) = self.game_assets.level(&self.level_definitions, value) else {
+                continue;
+            };
+            self.level.value = value;
+            hits = Some(definition.hits_to_advance);
+            self.event_level_changed.send(LevelChangedEvent(value));
+            if definition.rotate_skybox {
+                self.event_rotate_skybox.send(RotateSkyboxEvent());
+            }
+            if !definition.keep_planets {
+                for e in self.query_planet.iter(){
+                    self.commands.entity(e).despawn_recursive();
+                }
+            }
+            if definition.clear_opponents {
+                for e in self.query_opponent.iter(){
+                    self.commands.entity(e).despawn_recursive();
+                }
+            }
+            self.spawn_timer.0.set_duration(Duration::from_secs_f32(definition.spawn_interval));
+        }
+        hits
+    }
+}
+
+/// `setup` builds the first level, a later start level is entered from there.
+fn enter_start_level(
+    start_level: Res<StartLevel>,
+    mut level_entry: LevelEntry,
+    mut query_ship: Query<&mut Ship>,
+){
+    if start_level.0 <= level_entry.level() {
+        return;
+    }
+    let mut ship = query_ship.single_mut();
+    match level_entry.enter(start_level.0) {
+        Some(hits) => ship.hits = hits,
+        None => warn!("there is no level {}, starting at level {}", start_level.0, level_entry.level()),
+    }
+}
+
+fn change_level(
+    mut next_state: ResMut<NextState<GameState>>,
+    mut level_entry: LevelEntry,
+    mut query_ship: Query<&mut Ship>,
+){
+    let mut ship = query_ship.single_mut();
+    if  ship.hits <= 0 {
+        // a level with a boss only ends when the boss is destroyed
+        let has_boss = level_entry.game_assets.level(&level_entry.level_definitions, level_entry.level())
+            .is_some_and(|definition| definition.boss.is_some());
+        match *level_entry.boss_fight {
+            BossFight::None if has_boss => {
+                *level_entry.boss_fight = BossFight::Fighting;
+                return;
+            }
+            BossFight::Fighting => return,
+            _ => *level_entry.boss_fight = BossFight::None,
+        }
+        match level_entry.enter(level_entry.level() + 1) {
+            Some(hits) => ship.hits = hits,
+            None => {
+                // the last level is done
+                ship.win_or_lost = WinOrLostState::Win;
+                next_state.set(GameState::Won);
+            }
+        }
+    }
+}
+
+/// Hands out the weapons of every level reached, also the ones a game starts or continues at.
+pub fn unlock_weapons(
+    mut event_level_changed: EventReader<LevelChangedEvent>,
+    game_assets: Res<GameAssets>,
+    level_definitions: Res<Assets<LevelDefinition>>,
+    mut unlocked_weapons: ResMut<UnlockedWeapons>,
+){
+    for LevelChangedEvent(level) in event_level_changed.read() {
+        let Some(definition) = game_assets.level(&level_definitions, *level) else {
+            continue;
+        };
+        for weapon in &definition.unlocks {
+            unlocked_weapons.unlock(*weapon);
+            info!("unlocked {}", weapon.name());
+        }
+    }
+}
+
+fn count_time(
+    time:Res<Time>,
+    mut stats: ResMut<GameStats>
+){
+    stats.time += time.delta_seconds();
+}
+
+fn toggle_pause(
+    action_state: Res<ActionState>,
+    state: Res<State<GameState>>,
+    mut next_state: ResMut<NextState<GameState>>
+){
+    if action_state.just_pressed(Action::Pause) {
+        match state.get() {
+            GameState::Running => next_state.set(GameState::Paused),
+            GameState::Paused => next_state.set(GameState::Running),
+            _ => {}
+        }
+    }
+}
+
+fn pause_physics(
+    mut rapier_configuration: ResMut<RapierConfiguration>
+){
+    rapier_configuration.physics_pipeline_active = false;
+}
+
+fn resume_physics(
+    mut rapier_configuration: ResMut<RapierConfiguration>
+){
+    rapier_configuration.physics_pipeline_active = true;
+}
+
+/// Everything a new game starts from. The entities are gone already (see [`InGame`]).
+fn reset_game(
+    mut level: ResMut<Level>,
+    mut spawn_timer: ResMut<SpawnTimer>,
+    mut stats: ResMut<GameStats>,
+    mut score: ResMut<Score>,
+    mut boss_fight: ResMut<BossFight>,
+    mut canyon: ResMut<Canyon>,
+    mut active_power_ups: ResMut<ActivePowerUps>,
+    mut player_input: ResMut<PlayerInput>,
+    mut unlocked_weapons: ResMut<UnlockedWeapons>,
+    mut game_rng: ResMut<GameRng>,
+    mut query_skybox: Query<(&mut Skybox, &mut Transform)>
+){
+    *level = Level::default();
+    *spawn_timer = SpawnTimer::default();
+    *stats = GameStats::default();
+    *score = Score::default();
+    *boss_fight = BossFight::default();
+    *canyon = Canyon::default();
+    *active_power_ups = ActivePowerUps::default();
+    *player_input = PlayerInput::default();
+    *unlocked_weapons = UnlockedWeapons::default();
+    *game_rng = GameRng::new(game_rng.seed);
+    for (mut skybox, mut transform) in query_skybox.iter_mut() {
+        skybox.reset(&mut transform);
+    }
+}
+
+fn restart(
+    mut next_state: ResMut<NextState<GameState>>
+){
+    next_state.set(GameState::Running);
+}
+
+fn test_shields(
+    mut next_state: ResMut<NextState<GameState>>,
+    mut query: Query<(&mut Ship, &Health)>
+){
+    let (mut ship, health) = query.single_mut();
+    // damage skips a ship that is out of health, so exactly nothing left has to end it too
+    if !health.is_alive() {
+        next_state.set(GameState::End);
+        ship.win_or_lost = WinOrLostState::Lost;
+    }
+}
@@ -0,0 +1,93 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+const MAX_ENTRIES: usize = 10;
+const MAX_NAME_LENGTH: usize = 12;
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct HighScoreEntry {
+    pub name: String,
+    pub score: u32,
+    pub level: usize,
+}
+
+/// The best scores, highest first, kept in the user's data directory.
+#[derive(Resource, Serialize, Deserialize, Default, Clone, PartialEq, Debug)]
+pub struct HighScores {
+    pub entries: Vec<HighScoreEntry>,
+}
+
+impl HighScores {
+    /// `<data dir>/planet-rust/highscores.ron`, if the platform has a data directory.
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::data_dir().map(|dir| dir.join("planet-rust").join("highscores.ron"))
+    }
+
+    pub fn load(path: &Path) -> io::Result<HighScores> {
+        let text = fs::read_to_string(path)?;
+        ron::from_str(&text).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let text = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        fs::write(path, text)
+    }
+
+    pub fn qualifies(&self, score: u32) -> bool {
+        score > 0 && (self.entries.len() < MAX_ENTRIES
+            || self.entries.last().is_none_or(|entry| score > entry.score))
+    }
+
+    /// Adds the entry behind all equal scores and drops everything past the table size.
+    pub fn insert(&mut self, name: &str, score: u32, level: usize) {
+        let name: String = name.trim().chars().take(MAX_NAME_LENGTH).collect();
+        let index = self.entries.partition_point(|entry| entry.score >= score);
+        self.entries.insert(index, HighScoreEntry {
+            name: if name.is_empty() { "???".to_string() } else { name },
+            score,
+            level,
+        });
+        self.entries.truncate(MAX_ENTRIES);
+    }
+}
+
+/// Loads the high-score table at startup. Only the game binary adds this,
+/// so tests and headless runs never touch the user's files.
+pub struct HighScorePlugin;
+
+impl Plugin for HighScorePlugin {
+    fn build(&self, app: &mut App){
+        app.insert_resource(load_high_scores());
+    }
+}
+
+fn load_high_scores() -> HighScores {
+    let Some(path) = HighScores::default_path() else {
+        return HighScores::default();
+    };
+    match HighScores::load(&path) {
+        Ok(high_scores) => high_scores,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => HighScores::default(),
+        Err(e) => {
+            warn!("cannot read high scores from {}: {e}", path.display());
+            HighScores::default()
+        }
+    }
+}
+
+pub fn save_high_scores(high_scores: &HighScores) {
+    let Some(path) = HighScores::default_path() else {
+        warn!("no data directory, high scores are not saved");
+        return;
+    };
+    if let Err(e) = high_scores.save(&path) {
+        error!("cannot save high scores to {}: {e}", path.display());
+    }
+}
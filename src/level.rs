@@ -0,0 +1,194 @@
+use bevy::prelude::*;
+use rand::Rng;
+use rand::distributions::{Distribution, WeightedIndex};
+use serde::Deserialize;
+use crate::components::Flight;
+use crate::weapon::Weapon;
+
+/// One level of the campaign, loaded from `assets/levels/*.level.ron`.
+#[derive(Asset, TypePath, Deserialize, Debug, Clone)]
+pub struct LevelDefinition {
+    pub level: usize,
+    pub name: String,
+    pub spawn_interval: f32,
+    pub hits_to_advance: i32,
+    #[serde(default)]
+    pub rotate_skybox: bool,
+    #[serde(default)]
+    pub keep_planets: bool,
+    #[serde(default)]
+    pub clear_opponents: bool,
+    pub spawns: Vec<SpawnEntry>,
+    /// weapons the player gets when the level is reached
+    #[serde(default)]
+    pub unlocks: Vec<Weapon>,
+    /// file in `assets/sounds/music` played during the level, it crossfades into the next one
+    #[serde(default)]
+    pub music: Option<String>,
+    /// fought once `hits_to_advance` is reached, the level ends when it is destroyed
+    #[serde(default)]
+    pub boss: Option<BossDefinition>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct SpawnEntry {
+    #[serde(default = "default_weight")]
+    pub weight: f32,
+    pub kind: SpawnKind,
+}
+
+fn default_weight() -> f32 {
+    1.0
+}
+
+fn default_points() -> u32 {
+    100
+}
+
+/// What a spawn entry puts into the level. Ranges are `(min, max)`.
+#[derive(Deserialize, Debug, Clone)]
+pub enum SpawnKind {
+    Fighter {
+        speed: (f32, f32),
+        cooldown: (f32, f32),
+        hits: i32,
+        #[serde(default = "default_points")]
+        points: u32,
+        #[serde(default)]
+        flight: Flight,
+        #[serde(default)]
+        lead_shots: bool,
+        #[serde(default)]
+        weapon: Weapon,
+        /// share of every hit the armour takes away, see [`crate::components::Armour`]
+        #[serde(default)]
+        armour: f32,
+        /// hits the shield soaks up before the fighter takes damage
+        #[serde(default)]
+        shield: f32,
+    },
+    /// `hits` is for the biggest asteroid and `points` for the smallest, the others are in between.
+    /// Big asteroids split into smaller ones.
+    Asteroid {
+        speed: (f32, f32),
+        scale: (f32, f32),
+        hits: i32,
+        #[serde(default = "default_points")]
+        points: u32,
+        /// damage asteroids do to each other when they crash, 0 to only bounce
+        #[serde(default)]
+        chip_damage: f32,
+    },
+    /// A section of the valley, sometimes with a tower on its floor.
+    Platform {
+        speed: f32,
+        #[serde(default)]
+        towers: Option<TowerSpawn>,
+    },
+}
+
+#[derive(Deserialize, Debug, Clone, Copy)]
+pub struct TowerSpawn {
+    /// chance of a tower in a section
+    pub chance: f64,
+    pub hits: i32,
+    pub cooldown: (f32, f32),
+    #[serde(default = "default_points")]
+    pub points: u32,
+    #[serde(default)]
+    pub weapon: Weapon,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct BossDefinition {
+    pub name: String,
+    /// approach speed until it holds at `hold_z`
+    pub speed: f32,
+    pub hold_z: f32,
+    pub core_hits: i32,
+    pub points: u32,
+    pub weak_points: Vec<WeakPointDefinition>,
+    /// sorted from full health down
+    pub phases: Vec<BossPhase>,
+}
+
+impl BossDefinition {
+    /// The last phase whose `health` is reached, `health` is the fraction of the core's health left.
+    pub fn phase(&self, health: f32) -> usize {
+        self.phases.iter().rposition(|phase| health <= phase.health).unwrap_or(0)
+    }
+}
+
+/// A part of the boss that has to be shot off before the core takes hits.
+#[derive(Deserialize, Debug, Clone)]
+pub struct WeakPointDefinition {
+    pub offset: (f32, f32, f32),
+    pub hits: i32,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct BossPhase {
+    /// starts when the core is down to this fraction of its health
+    pub health: f32,
+    pub attack: AttackPattern,
+    pub cooldown: f32,
+    /// sideways swing
+    #[serde(default)]
+    pub sway: f32,
+}
+
+/// The shots of one boss volley, angles in degrees.
+#[derive(Deserialize, Debug, Clone, Copy)]
+pub enum AttackPattern {
+    /// a single shot at the ship
+    Aimed,
+    /// `count` shots fanned out sideways over `angle`
+    Spread { count: usize, angle: f32 },
+    /// `count` shots on a cone of `angle` around the ship, turning by `spin` degrees per second
+    Ring {
+        count: usize,
+        angle: f32,
+        #[serde(default)]
+        spin: f32,
+    },
+}
+
+impl AttackPattern {
+    /// Shot directions for a volley towards `aim` (normalized), `time` turns a spinning ring.
+    pub fn directions(&self, aim: Vec3, time: f32) -> Vec<Vec3> {
+        match *self {
+            AttackPattern::Aimed => vec![aim],
+            AttackPattern::Spread { count, angle } => (0..count)
+                .map(|i| {
+                    let t = if count > 1 { i as f32 / (count - 1) as f32 - 0.5 } else { 0.0 };
+                    Quat::from_rotation_y((angle * t).to_radians()) * aim
+                })
+                .collect(),
+            AttackPattern::Ring { count, angle, spin } => {
+                let (u, v) = aim.any_orthonormal_pair();
+                let (sin, cos) = angle.to_radians().sin_cos();
+                (0..count)
+                    .map(|i| {
+                        let around = i as f32 / count as f32 * std::f32::consts::TAU + (spin * time).to_radians();
+                        (aim * cos + (u * around.cos() + v * around.sin()) * sin).normalize()
+                    })
+                    .collect()
+            }
+        }
+    }
+}
+
+impl LevelDefinition {
+    pub fn choose_spawn<R: Rng>(&self, rng: &mut R) -> Option<&SpawnEntry> {
+        let weights = WeightedIndex::new(self.spawns.iter().map(|s| s.weight)).ok()?;
+        self.spawns.get(weights.sample(rng))
+    }
+}
+
+pub fn sample_range<R: Rng>(rng: &mut R, range: (f32, f32)) -> f32 {
+    if range.1 > range.0 {
+        rng.gen_range(range.0..=range.1)
+    } else {
+        range.0
+    }
+}
@@ -0,0 +1,34 @@
+#![allow(clippy::too_many_arguments, clippy::type_complexity)]
+
+#[cfg(feature = "debug")]
+pub mod orbitcamera;
+#[cfg(feature = "debug")]
+pub mod gamedebug;
+#[cfg(feature = "debug")]
+pub mod console;
+pub mod skybox;
+pub mod components;
+pub mod game_state;
+pub mod resources;
+pub mod collision;
+pub mod damage;
+pub mod asteroid;
+pub mod boss;
+pub mod powerup;
+pub mod weapon;
+pub mod pool;
+pub mod particles;
+pub mod audio;
+pub mod canyon;
+pub mod events;
+pub mod level;
+pub mod rng;
+pub mod gameplay;
+pub mod menu;
+pub mod sim;
+pub mod replay;
+pub mod highscore;
+pub mod actions;
+pub mod cli;
+pub mod settings;
+pub mod save;
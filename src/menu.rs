@@ -0,0 +1,376 @@
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+use crate::actions::{Action, InputBindings, Rebinding};
+use crate::audio::AudioSettings;
+use crate::game_state::GameState;
+use crate::highscore::{save_high_scores, HighScores};
+use crate::replay::ReplayRecorder;
+use crate::resources::{Level, Score};
+use crate::save::{Resume, SaveSlots};
+use crate::settings::{Antialiasing, Resolution, Settings};
+
+/// Main menu, pause menu and the buttons on the end screens.
+/// Needs the `HighScores` from `HighScorePlugin`, the `AudioSettings` from `GameAudioPlugin`,
+/// the `Settings` from `SettingsPlugin` and the save slots from `SavePlugin`.
+pub struct MenuPlugin;
+
+/// Whether the controls window is open, it is opened from the options.
+#[derive(Resource, Default)]
+struct ControlsWindow {
+    open: bool,
+}
+
+/// Whether the options window is open in the main or the pause menu.
+#[derive(Resource, Default)]
+struct OptionsWindow {
+    open: bool,
+}
+
+/// The name typed on the end screen for a new high score.
+#[derive(Resource, Default)]
+struct NameEntry {
+    name: String,
+    saved: bool,
+}
+
+impl Plugin for MenuPlugin {
+    fn build(&self, app: &mut App){
+        app
+            .init_resource::<NameEntry>()
+            .init_resource::<ControlsWindow>()
+            .init_resource::<OptionsWindow>()
+            .add_systems(OnExit(GameState::MainMenu), (close_controls, close_options))
+            .add_systems(OnExit(GameState::Paused), (close_controls, close_options))
+            .add_systems(Update, (controls_window, options_window)
+                .run_if(in_state(GameState::MainMenu).or_else(in_state(GameState::Paused))))
+            .add_systems(Update, save_bindings.run_if(resource_changed::<InputBindings>
+                .and_then(not(resource_added::<InputBindings>))))
+            .add_systems(Update, save_audio_settings.run_if(resource_changed::<AudioSettings>
+                .and_then(not(resource_added::<AudioSettings>))))
+            .add_systems(Update, save_settings.run_if(resource_changed::<Settings>
+                .and_then(not(resource_added::<Settings>))))
+            .add_systems(OnEnter(GameState::End), reset_name_entry)
+            .add_systems(OnEnter(GameState::Won), reset_name_entry)
+            .add_systems(Update, main_menu.run_if(in_state(GameState::MainMenu)))
+            .add_systems(Update, pause_menu.run_if(in_state(GameState::Paused)))
+            .add_systems(Update, game_over_menu.run_if(in_state(GameState::End).or_else(in_state(GameState::Won))));
+    }
+}
+
+const BUTTON_SIZE: [f32; 2] = [240.0, 40.0];
+
+fn menu_window(title: &str) -> egui::Window<'static> {
+    egui::Window::new(title)
+        .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+        .collapsible(false)
+        .resizable(false)
+}
+
+fn high_score_table(ui: &mut egui::Ui, high_scores: &HighScores) {
+    if high_scores.entries.is_empty() {
+        ui.label("No high scores yet");
+        return;
+    }
+    egui::Grid::new("high_scores").striped(true).show(ui, |ui| {
+        for (rank, entry) in high_scores.entries.iter().enumerate() {
+            ui.label(format!("{}.", rank + 1));
+            ui.label(&entry.name);
+            ui.label(format!("{}", entry.score));
+            ui.label(format!("level {}", entry.level));
+            ui.end_row();
+        }
+    });
+}
+
+fn main_menu(
+    mut egui_context: EguiContexts,
+    high_scores: Res<HighScores>,
+    mut save_slots: ResMut<SaveSlots>,
+    mut resume: ResMut<Resume>,
+    recorder: Option<Res<ReplayRecorder>>,
+    controls: Res<ControlsWindow>,
+    mut options: ResMut<OptionsWindow>,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut exit: EventWriter<AppExit>
+){
+    if controls.open || options.open {
+        return;
+    }
+    if keyboard_input.just_pressed(KeyCode::Enter) {
+        next_state.set(GameState::Running);
+    }
+    menu_window("planet rust").show(egui_context.ctx_mut(), |ui| {
+        ui.vertical_centered(|ui| {
+            ui.horizontal(|ui| {
+                for slot in 0..save_slots.slots.len() {
+                    ui.selectable_value(&mut save_slots.active, slot, format!("Slot {}", slot + 1));
+                }
+            });
+            let save = save_slots.slots[save_slots.active].as_ref();
+            let label = save.map_or("Continue".to_string(), |save| format!("Continue - level {}", save.level));
+            // a replay starts from a new game, it does not know the saved one
+            let button = ui.add_enabled(save.is_some() && recorder.is_none(), egui::Button::new(label).min_size(BUTTON_SIZE.into()))
+                .on_disabled_hover_text(if recorder.is_some() { "Not while recording a replay" } else { "Nothing saved in this slot" });
+            if button.clicked() {
+                resume.0 = save.cloned();
+                next_state.set(GameState::Running);
+            }
+            if ui.add_sized(BUTTON_SIZE, egui::Button::new("Start")).clicked() {
+                next_state.set(GameState::Running);
+            }
+            if ui.add_sized(BUTTON_SIZE, egui::Button::new("Options")).clicked() {
+                options.open = true;
+            }
+            if ui.add_sized(BUTTON_SIZE, egui::Button::new("Quit")).clicked() {
+                exit.send(AppExit::Success);
+            }
+            ui.separator();
+            high_score_table(ui, &high_scores);
+        });
+    });
+}
+
+fn pause_menu(
+    mut egui_context: EguiContexts,
+    controls: Res<ControlsWindow>,
+    mut options: ResMut<OptionsWindow>,
+    mut next_state: ResMut<NextState<GameState>>
+){
+    if controls.open || options.open {
+        return;
+    }
+    menu_window("Paused").show(egui_context.ctx_mut(), |ui| {
+        ui.vertical_centered(|ui| {
+            if ui.add_sized(BUTTON_SIZE, egui::Button::new("Resume")).clicked() {
+                next_state.set(GameState::Running);
+            }
+            if ui.add_sized(BUTTON_SIZE, egui::Button::new("Options")).clicked() {
+                options.open = true;
+            }
+            if ui.add_sized(BUTTON_SIZE, egui::Button::new("Restart")).clicked() {
+                next_state.set(GameState::Restart);
+            }
+            if ui.add_sized(BUTTON_SIZE, egui::Button::new("Main Menu")).clicked() {
+                next_state.set(GameState::MainMenu);
+            }
+        });
+    });
+}
+
+fn controls_window(
+    mut egui_context: EguiContexts,
+    mut controls: ResMut<ControlsWindow>,
+    mut options: ResMut<OptionsWindow>,
+    mut bindings: ResMut<InputBindings>,
+    mut rebinding: ResMut<Rebinding>
+){
+    if !controls.open {
+        return;
+    }
+    menu_window("Controls").show(egui_context.ctx_mut(), |ui| {
+        egui::Grid::new("bindings").striped(true).show(ui, |ui| {
+            for action in Action::ALL {
+                ui.label(format!("{action:?}"));
+                if rebinding.action == Some(action) {
+                    ui.label(match (action.is_axis(), rebinding.waiting_for_positive()) {
+                        (false, _) => "press a key or button",
+                        (true, false) => "move a stick, or press the negative key",
+                        (true, true) => "press the positive key",
+                    });
+                    if ui.button("Cancel").clicked() {
+                        rebinding.cancel();
+                    }
+                } else {
+                    let names: Vec<String> = bindings.bindings(action).iter().map(|b| b.to_string()).collect();
+                    ui.label(names.join(", "));
+                    if ui.button("Rebind").clicked() {
+                        rebinding.start(action);
+                    }
+                }
+                ui.end_row();
+            }
+        });
+        ui.vertical_centered(|ui| {
+            if ui.add_sized(BUTTON_SIZE, egui::Button::new("Reset to defaults")).clicked() {
+                rebinding.cancel();
+                *bindings = InputBindings::default();
+            }
+            if ui.add_sized(BUTTON_SIZE, egui::Button::new("Back")).clicked() {
+                rebinding.cancel();
+                controls.open = false;
+                options.open = true;
+            }
+        });
+    });
+}
+
+fn options_window(
+    mut egui_context: EguiContexts,
+    mut options: ResMut<OptionsWindow>,
+    mut controls: ResMut<ControlsWindow>,
+    mut settings: ResMut<Settings>,
+    mut audio_settings: ResMut<AudioSettings>
+){
+    if !options.open {
+        return;
+    }
+    menu_window("Options").show(egui_context.ctx_mut(), |ui| {
+        // only touch the settings when something is changed, so they are not saved every frame
+        let mut edited = *settings;
+        let mut edited_audio = *audio_settings;
+        ui.heading("Graphics");
+        egui::Grid::new("graphics").show(ui, |ui| {
+            ui.label("Resolution");
+            egui::ComboBox::from_id_source("resolution")
+                .selected_text(edited.resolution.to_string())
+                .show_ui(ui, |ui| {
+                    for resolution in Resolution::PRESETS {
+                        ui.selectable_value(&mut edited.resolution, resolution, resolution.to_string());
+                    }
+                });
+            ui.end_row();
+            ui.label("Anti-aliasing");
+            egui::ComboBox::from_id_source("antialiasing")
+                .selected_text(format!("{:?}", edited.antialiasing))
+                .show_ui(ui, |ui| {
+                    for antialiasing in Antialiasing::ALL {
+                        ui.selectable_value(&mut edited.antialiasing, antialiasing, format!("{antialiasing:?}"));
+                    }
+                });
+            ui.end_row();
+            for (name, value) in [("Fullscreen", &mut edited.fullscreen), ("Vsync", &mut edited.vsync),
+                                  ("Shadows", &mut edited.shadows)] {
+                ui.label(name);
+                ui.checkbox(value, "");
+                ui.end_row();
+            }
+        });
+        ui.separator();
+        ui.heading("Audio");
+        egui::Grid::new("volumes").show(ui, |ui| {
+            for (name, volume) in [("Master", &mut edited_audio.master), ("Music", &mut edited_audio.music),
+                                   ("Effects", &mut edited_audio.sfx)] {
+                ui.label(name);
+                ui.add(egui::Slider::new(volume, 0.0..=1.0).show_value(false));
+                ui.end_row();
+            }
+        });
+        if edited != *settings {
+            *settings = edited;
+        }
+        if edited_audio != *audio_settings {
+            *audio_settings = edited_audio;
+        }
+        ui.separator();
+        ui.vertical_centered(|ui| {
+            if ui.add_sized(BUTTON_SIZE, egui::Button::new("Controls")).clicked() {
+                options.open = false;
+                controls.open = true;
+            }
+            if ui.add_sized(BUTTON_SIZE, egui::Button::new("Back")).clicked() {
+                options.open = false;
+            }
+        });
+    });
+}
+
+fn close_options(
+    mut options: ResMut<OptionsWindow>
+){
+    options.open = false;
+}
+
+fn save_settings(
+    settings: Res<Settings>
+){
+    let Some(path) = Settings::default_path() else {
+        warn!("no config directory, settings are not saved");
+        return;
+    };
+    if let Err(e) = settings.save(&path) {
+        error!("cannot save settings to {}: {e}", path.display());
+    }
+}
+
+fn save_audio_settings(
+    settings: Res<AudioSettings>
+){
+    let Some(path) = AudioSettings::default_path() else {
+        warn!("no config directory, audio settings are not saved");
+        return;
+    };
+    if let Err(e) = settings.save(&path) {
+        error!("cannot save audio settings to {}: {e}", path.display());
+    }
+}
+
+fn close_controls(
+    mut controls: ResMut<ControlsWindow>,
+    mut rebinding: ResMut<Rebinding>
+){
+    controls.open = false;
+    rebinding.cancel();
+}
+
+fn save_bindings(
+    bindings: Res<InputBindings>
+){
+    let Some(path) = InputBindings::default_path() else {
+        warn!("no config directory, key bindings are not saved");
+        return;
+    };
+    if let Err(e) = bindings.save(&path) {
+        error!("cannot save key bindings to {}: {e}", path.display());
+    }
+}
+
+fn reset_name_entry(
+    mut name_entry: ResMut<NameEntry>
+){
+    name_entry.saved = false;
+}
+
+fn game_over_menu(
+    mut egui_context: EguiContexts,
+    score: Res<Score>,
+    level: Res<Level>,
+    mut high_scores: ResMut<HighScores>,
+    mut name_entry: ResMut<NameEntry>,
+    mut next_state: ResMut<NextState<GameState>>
+){
+    egui::Window::new("High Scores")
+        .anchor(egui::Align2::RIGHT_TOP, egui::Vec2::new(-20.0, 80.0))
+        .collapsible(false)
+        .resizable(false)
+        .show(egui_context.ctx_mut(), |ui| {
+            if !name_entry.saved && high_scores.qualifies(score.points) {
+                ui.label(format!("New high score: {}", score.points));
+                ui.horizontal(|ui| {
+                    let response = ui.text_edit_singleline(&mut name_entry.name);
+                    let entered = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                    if ui.button("Save").clicked() || entered {
+                        high_scores.insert(&name_entry.name, score.points, level.value);
+                        save_high_scores(&high_scores);
+                        name_entry.saved = true;
+                    }
+                });
+                ui.separator();
+            }
+            high_score_table(ui, &high_scores);
+        });
+    egui::Window::new("Game over")
+        .anchor(egui::Align2::CENTER_BOTTOM, egui::Vec2::new(0.0, -40.0))
+        .title_bar(false)
+        .resizable(false)
+        .show(egui_context.ctx_mut(), |ui| {
+            ui.horizontal(|ui| {
+                if ui.add_sized(BUTTON_SIZE, egui::Button::new("Restart")).clicked() {
+                    next_state.set(GameState::Restart);
+                }
+                if ui.add_sized(BUTTON_SIZE, egui::Button::new("Main Menu")).clicked() {
+                    next_state.set(GameState::MainMenu);
+                }
+            });
+        });
+}
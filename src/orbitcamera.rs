@@ -95,16 +95,12 @@ fn emit_motion_events(
     }
 
     for camera in query.iter_mut() {
-        if camera.enabled {
+        if camera.enabled && send_event {
             if mouse_button_input.pressed(camera.rotate_button){
-                if send_event {
-                    events.send(CameraEvents::Orbit(delta));
-                }
+                events.send(CameraEvents::Orbit(delta));
             }
             if mouse_button_input.pressed(camera.pan_button){
-                if send_event {
-                    events.send(CameraEvents::Pan(delta));
-                }
+                events.send(CameraEvents::Pan(delta));
             }
         }
     }
@@ -0,0 +1,261 @@
+use std::f32::consts::{PI, TAU};
+use bevy::prelude::*;
+use bevy::color::palettes::css::{GOLD, ORANGE, ORANGE_RED, WHITE, YELLOW};
+use bevy_rapier3d::prelude::Collider;
+use rand::Rng;
+use crate::components::{Shield, Ship};
+use crate::events::{CreateEffectEvent, DamageEvent, DamageKind};
+use crate::game_state::InGame;
+use crate::pool::{EntityPool, PoolCommands, PoolKind, SharedHandles};
+use crate::rng::GameRng;
+
+/// Particles of an explosion the size of a fighter, bigger ones get more.
+const EXPLOSION_PARTICLES: f32 = 32.0;
+const MAX_EXPLOSION_PARTICLES: u32 = 256;
+const SPARK_PARTICLES: u32 = 16;
+
+/// Spawns particles where its entity is. Nothing it spawns is a physics body,
+/// they all share one mesh and a few materials so they are drawn instanced.
+#[derive(Component, Clone)]
+pub struct ParticleEmitter {
+    /// particles per second, 0 if it only bursts
+    pub rate: f32,
+    /// particles spawned at once when the emitter starts
+    pub burst: u32,
+    /// seconds the emitter keeps going, it is despawned afterwards. `None` runs forever
+    pub duration: Option<f32>,
+    /// where particles start, relative to the entity
+    pub offset: Vec3,
+    /// the middle of the cone particles fly out in, relative to the entity
+    pub direction: Vec3,
+    /// half the opening of the cone in radians, [`PI`] for every direction
+    pub cone: f32,
+    pub speed: (f32, f32),
+    /// seconds a particle lives
+    pub lifetime: f32,
+    /// colour at birth and at death
+    pub color: (Color, Color),
+    /// size at birth and at death
+    pub size: (f32, f32),
+    pub gravity: Vec3,
+    /// fraction of the speed lost per second
+    pub drag: f32,
+    pub started: bool,
+    pub elapsed: f32,
+    /// particles owed by the rate, spawned once they add up to a whole one
+    pub pending: f32,
+}
+
+impl Default for ParticleEmitter {
+    fn default() -> Self {
+        ParticleEmitter {
+            rate: 0.0,
+            burst: 0,
+            duration: None,
+            offset: Vec3::ZERO,
+            direction: Vec3::Z,
+            cone: PI,
+            speed: (0.0, 0.0),
+            lifetime: 1.0,
+            color: (Color::WHITE, Color::WHITE),
+            size: (1.0, 1.0),
+            gravity: Vec3::ZERO,
+            drag: 0.0,
+            started: false,
+            elapsed: 0.0,
+            pending: 0.0,
+        }
+    }
+}
+
+impl ParticleEmitter {
+    /// Debris flying apart, `size` is about the radius of what blew up.
+    pub fn explosion(size: f32) -> Self {
+        let size = size.max(0.25);
+        ParticleEmitter {
+            burst: ((EXPLOSION_PARTICLES * size) as u32).clamp(8, MAX_EXPLOSION_PARTICLES),
+            duration: Some(0.0),
+            speed: (20.0 * size.sqrt(), 100.0 * size.sqrt()),
+            lifetime: 1.5,
+            color: (Color::Srgba(YELLOW), Color::Srgba(ORANGE_RED)),
+            size: (0.4 * size.sqrt(), 0.1),
+            drag: 1.0,
+            ..default()
+        }
+    }
+
+    /// The flame behind the ship.
+    pub fn exhaust() -> Self {
+        ParticleEmitter {
+            rate: 60.0,
+            offset: Vec3::new(0.0, 0.0, 3.0),
+            direction: Vec3::Z,
+            cone: 0.15,
+            speed: (30.0, 40.0),
+            lifetime: 0.3,
+            color: (Color::Srgba(GOLD), Color::Srgba(ORANGE_RED)),
+            size: (0.5, 0.1),
+            ..default()
+        }
+    }
+
+    /// A short spray where a shield deflects a hit.
+    pub fn sparks() -> Self {
+        ParticleEmitter {
+            burst: SPARK_PARTICLES,
+            duration: Some(0.0),
+            speed: (20.0, 50.0),
+            lifetime: 0.4,
+            color: (Color::Srgba(WHITE), Color::Srgba(ORANGE)),
+            size: (0.2, 0.05),
+            gravity: Vec3::new(0.0, -40.0, 0.0),
+            drag: 3.0,
+            ..default()
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct Particle {
+    pub velocity: Vec3,
+    pub age: f32,
+    pub lifetime: f32,
+    pub size: (f32, f32),
+    pub gravity: Vec3,
+    pub drag: f32,
+    /// the colour ramp in [`SharedHandles`] and the step shown now
+    pub ramp: usize,
+    pub step: usize,
+}
+
+/// Half the width of a fighter, explosions are sized relative to it.
+const FIGHTER_SIZE: f32 = 3.0;
+
+/// How big the explosion of something with this collider is, 1 for a fighter.
+pub fn effect_size(collider: Option<&Collider>) -> f32 {
+    collider.map_or(1.0, |collider| collider.raw.compute_local_aabb().half_extents().max() / FIGHTER_SIZE)
+}
+
+/// Explosions are one-shot emitters that go away once they burst.
+pub fn create_effect(
+    mut commands: Commands,
+    mut event_create_effect: EventReader<CreateEffectEvent>,
+) {
+    for event in event_create_effect.read() {
+        spawn_emitter(&mut commands, event.0, ParticleEmitter::explosion(event.1));
+    }
+}
+
+/// Shields that take a projectile throw sparks, the ship's whole shielding counts.
+pub fn spark_shield_hits(
+    mut commands: Commands,
+    mut event_damage: EventReader<DamageEvent>,
+    query: Query<(&Transform, Option<&Shield>, Has<Ship>)>,
+) {
+    for event in event_damage.read() {
+        if event.kind != DamageKind::Projectile {
+            continue;
+        }
+        let Ok((transform, shield, ship)) = query.get(event.target) else {
+            continue;
+        };
+        if ship || shield.is_some_and(|shield| shield.current > 0.0) {
+            spawn_emitter(&mut commands, transform.translation, ParticleEmitter::sparks());
+        }
+    }
+}
+
+pub fn spawn_emitter(commands: &mut Commands, translation: Vec3, emitter: ParticleEmitter) {
+    commands.spawn((
+        TransformBundle::from_transform(Transform::from_translation(translation)),
+        emitter,
+        Name::new("ParticleEmitter"),
+        StateScoped(InGame),
+    ));
+}
+
+pub fn emit_particles(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut pool: ResMut<EntityPool>,
+    mut handles: ResMut<SharedHandles>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut game_rng: ResMut<GameRng>,
+    mut query: Query<(Entity, &Transform, &mut ParticleEmitter)>,
+) {
+    let rng = &mut game_rng.effects;
+    for (entity, transform, mut emitter) in query.iter_mut() {
+        let mut count = if emitter.started { 0 } else { emitter.burst };
+        emitter.started = true;
+        emitter.pending += emitter.rate * time.delta_seconds();
+        count += emitter.pending as u32;
+        emitter.pending = emitter.pending.fract();
+
+        if count > 0 {
+            let ramp = handles.color_ramp(&mut materials, emitter.color.0, emitter.color.1);
+            let origin = transform.transform_point(emitter.offset);
+            let axis = transform.rotation * emitter.direction;
+            for _ in 0..count {
+                let velocity = sample_cone(rng, axis, emitter.cone) * rng.gen_range(emitter.speed.0..=emitter.speed.1);
+                let particle = pool.acquire(&mut commands, PoolKind::Particle);
+                commands.entity(particle)
+                    .insert(PbrBundle {
+                        mesh: handles.cube.clone(),
+                        material: handles.ramp_material(ramp, 0),
+                        transform: Transform::from_translation(origin).with_scale(Vec3::splat(emitter.size.0)),
+                        ..default()
+                    })
+                    .insert(Particle {
+                        velocity,
+                        age: 0.0,
+                        lifetime: emitter.lifetime,
+                        size: emitter.size,
+                        gravity: emitter.gravity,
+                        drag: emitter.drag,
+                        ramp,
+                        step: 0,
+                    });
+            }
+        }
+
+        emitter.elapsed += time.delta_seconds();
+        if emitter.duration.is_some_and(|duration| emitter.elapsed >= duration) {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+/// Moves, shrinks and recolours particles, and puts them back when they are old.
+pub fn update_particles(
+    mut commands: Commands,
+    time: Res<Time>,
+    handles: Res<SharedHandles>,
+    mut query: Query<(Entity, &mut Transform, &mut Particle, &mut Handle<StandardMaterial>)>,
+) {
+    let delta = time.delta_seconds();
+    for (entity, mut transform, mut particle, mut material) in query.iter_mut() {
+        particle.age += delta;
+        if particle.age >= particle.lifetime {
+            commands.release(entity);
+            continue;
+        }
+        let t = particle.age / particle.lifetime;
+        particle.velocity = (particle.velocity + particle.gravity * delta) * (1.0 - particle.drag * delta).max(0.0);
+        transform.translation += particle.velocity * delta;
+        transform.scale = Vec3::splat(particle.size.0.lerp(particle.size.1, t));
+        let step = handles.ramp_step(t);
+        if step != particle.step {
+            particle.step = step;
+            *material = handles.ramp_material(particle.ramp, step);
+        }
+    }
+}
+
+/// A random direction at most `angle` away from `axis`, evenly spread.
+fn sample_cone<R: Rng>(rng: &mut R, axis: Vec3, angle: f32) -> Vec3 {
+    let cos = rng.gen_range(angle.min(PI).cos()..=1.0);
+    let sin = (1.0 - cos * cos).sqrt();
+    let around = rng.gen_range(0.0..TAU);
+    let local = Vec3::new(sin * around.cos(), sin * around.sin(), cos);
+    Quat::from_rotation_arc(Vec3::Z, axis.normalize_or(Vec3::Z)) * local
+}
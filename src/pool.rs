@@ -0,0 +1,227 @@
+use bevy::ecs::world::Command;
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use bevy_rapier3d::prelude::*;
+use crate::components::{Damage, Despawnable, Homing, Laser, Terrain};
+use crate::particles::Particle;
+
+/// Steps of a colour ramp, particles change colour this many times over their life.
+pub const RAMP_STEPS: usize = 8;
+
+/// Meshes and materials shared by every projectile, pickup, particle and valley segment,
+/// so firing, exploding and flying through the valley do not add assets.
+#[derive(Resource)]
+pub struct SharedHandles {
+    /// 1×1×1, scaled to the size of the box
+    pub cube: Handle<Mesh>,
+    /// radius 1, scaled to the size of the ball
+    pub sphere: Handle<Mesh>,
+    glowing: HashMap<[u8; 4], Handle<StandardMaterial>>,
+    ramps: Vec<Vec<Handle<StandardMaterial>>>,
+    ramp_ids: HashMap<([u8; 4], [u8; 4]), usize>,
+    textured: HashMap<AssetId<Image>, Handle<StandardMaterial>>,
+}
+
+impl SharedHandles {
+    /// A glowing material in `color` for projectiles and pickups, made the first time it is asked for.
+    pub fn glowing_material(&mut self, materials: &mut Assets<StandardMaterial>, color: Color) -> Handle<StandardMaterial> {
+        self.glowing
+            .entry(color.to_srgba().to_u8_array())
+            .or_insert_with(|| materials.add(StandardMaterial {
+                base_color: color,
+                emissive: color.into(),
+                ..default()
+            }))
+            .clone()
+    }
+
+    /// A material showing `texture`, for the valley tiles, made the first time it is asked for.
+    pub fn textured_material(&mut self, materials: &mut Assets<StandardMaterial>, texture: &Handle<Image>) -> Handle<StandardMaterial> {
+        self.textured
+            .entry(texture.id())
+            .or_insert_with(|| materials.add(StandardMaterial {
+                base_color_texture: Some(texture.clone()),
+                ..default()
+            }))
+            .clone()
+    }
+
+    /// How many materials were made on demand so far, there is a fixed number per colour and texture.
+    pub fn cached_materials(&self) -> usize {
+        self.glowing.len() + self.ramps.len() * RAMP_STEPS + self.textured.len()
+    }
+
+    /// Unlit materials fading from `from` to `to` in [`RAMP_STEPS`], made the first time
+    /// they are asked for. Returns the ramp for [`Self::ramp_material`].
+    pub fn color_ramp(&mut self, materials: &mut Assets<StandardMaterial>, from: Color, to: Color) -> usize {
+        let key = (from.to_srgba().to_u8_array(), to.to_srgba().to_u8_array());
+        if let Some(ramp) = self.ramp_ids.get(&key) {
+            return *ramp;
+        }
+        let steps = (0..RAMP_STEPS)
+            .map(|step| {
+                let color = from.mix(&to, step as f32 / (RAMP_STEPS - 1) as f32);
+                materials.add(StandardMaterial {
+                    base_color: color,
+                    emissive: color.into(),
+                    unlit: true,
+                    ..default()
+                })
+            })
+            .collect();
+        self.ramps.push(steps);
+        self.ramp_ids.insert(key, self.ramps.len() - 1);
+        self.ramps.len() - 1
+    }
+
+    pub fn ramp_material(&self, ramp: usize, step: usize) -> Handle<StandardMaterial> {
+        self.ramps[ramp][step.min(RAMP_STEPS - 1)].clone()
+    }
+
+    /// The step of a ramp for `t` between 0 and 1.
+    pub fn ramp_step(&self, t: f32) -> usize {
+        (t.clamp(0.0, 1.0) * (RAMP_STEPS - 1) as f32).round() as usize
+    }
+}
+
+pub fn setup_shared_handles(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+) {
+    commands.insert_resource(SharedHandles {
+        cube: meshes.add(Cuboid::new(1.0, 1.0, 1.0)),
+        sphere: meshes.add(Sphere::new(1.0)),
+        glowing: HashMap::new(),
+        ramps: Vec::new(),
+        ramp_ids: HashMap::new(),
+        textured: HashMap::new(),
+    });
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PoolKind {
+    Laser,
+    Particle,
+    /// a piece of the valley, see [`crate::canyon::Segment`]
+    Segment,
+}
+
+/// Marks an entity that is recycled instead of despawned, see [`PoolCommands::release`].
+#[derive(Component)]
+pub struct Pooled {
+    pub kind: PoolKind,
+    pub active: bool,
+}
+
+/// Hidden, disabled entities waiting to be used again.
+#[derive(Resource, Default)]
+pub struct EntityPool {
+    lasers: Vec<Entity>,
+    particles: Vec<Entity>,
+    segments: Vec<Entity>,
+}
+
+impl EntityPool {
+    fn free(&mut self, kind: PoolKind) -> &mut Vec<Entity> {
+        match kind {
+            PoolKind::Laser => &mut self.lasers,
+            PoolKind::Particle => &mut self.particles,
+            PoolKind::Segment => &mut self.segments,
+        }
+    }
+
+    /// A free entity of that kind, or a new one if there is none left.
+    /// Either way the caller inserts everything it needs to look and move like new.
+    pub fn acquire(&mut self, commands: &mut Commands, kind: PoolKind) -> Entity {
+        match self.free(kind).pop() {
+            Some(entity) => {
+                commands.entity(entity)
+                    .insert(Pooled { kind, active: true })
+                    .remove::<(RigidBodyDisabled, ColliderDisabled)>();
+                entity
+            }
+            None => commands.spawn(Pooled { kind, active: true }).id(),
+        }
+    }
+
+    pub fn free_count(&self, kind: PoolKind) -> usize {
+        match kind {
+            PoolKind::Laser => self.lasers.len(),
+            PoolKind::Particle => self.particles.len(),
+            PoolKind::Segment => self.segments.len(),
+        }
+    }
+}
+
+pub trait PoolCommands {
+    /// Puts a pooled entity back into its pool, anything else is despawned.
+    fn release(&mut self, entity: Entity);
+
+    /// Gives `entity` the shape of `mesh`. A recycled entity keeps its mesh asset and only
+    /// its contents change, for shapes that cannot share one mesh like the valley segments.
+    fn set_mesh(&mut self, entity: Entity, mesh: Mesh);
+}
+
+impl PoolCommands for Commands<'_, '_> {
+    fn release(&mut self, entity: Entity) {
+        self.add(Release(entity));
+    }
+
+    fn set_mesh(&mut self, entity: Entity, mesh: Mesh) {
+        self.add(SetMesh(entity, mesh));
+    }
+}
+
+struct Release(Entity);
+
+impl Command for Release {
+    fn apply(self, world: &mut World) {
+        let Some(mut entity) = world.get_entity_mut(self.0) else {
+            return;
+        };
+        let Some(mut pooled) = entity.get_mut::<Pooled>() else {
+            entity.despawn_recursive();
+            return;
+        };
+        // hit twice in the same frame
+        if !pooled.active {
+            return;
+        }
+        pooled.active = false;
+        let kind = pooled.kind;
+        entity
+            .remove::<(Laser, Damage, Homing, Despawnable, Particle, Terrain)>()
+            .insert((Visibility::Hidden, Velocity::zero(), RigidBodyDisabled, ColliderDisabled));
+        world.resource_mut::<EntityPool>().free(kind).push(self.0);
+    }
+}
+
+struct SetMesh(Entity, Mesh);
+
+impl Command for SetMesh {
+    fn apply(self, world: &mut World) {
+        let Some(handle) = world.get_entity(self.0).map(|entity| entity.get::<Handle<Mesh>>().cloned()) else {
+            return;
+        };
+        let mut meshes = world.resource_mut::<Assets<Mesh>>();
+        match handle {
+            Some(handle) => meshes.insert(&handle, self.1),
+            None => {
+                let handle = meshes.add(self.1);
+                world.entity_mut(self.0).insert(handle);
+            }
+        }
+    }
+}
+
+/// Pooled entities outlive a game, everything still flying is put back when it ends.
+pub fn release_pooled(
+    mut commands: Commands,
+    query: Query<(Entity, &Pooled)>,
+) {
+    for (entity, pooled) in query.iter() {
+        if pooled.active {
+            commands.release(entity);
+        }
+    }
+}
@@ -0,0 +1,125 @@
+use bevy::prelude::*;
+use bevy::color::palettes::css::{DEEP_SKY_BLUE, GOLD, MAGENTA, ORANGE};
+use bevy_rapier3d::prelude::*;
+use rand::Rng;
+use crate::components::{Despawnable, LaserGun, PowerUp, PowerUpKind, Ship};
+use crate::events::OpponentDestroyedEvent;
+use crate::game_state::InGame;
+use crate::pool::SharedHandles;
+use crate::resources::ActivePowerUps;
+use crate::rng::GameRng;
+
+/// How often a destroyed opponent leaves a pickup behind.
+const DROP_CHANCE: f64 = 0.15;
+const PICKUP_SPEED: f32 = 40.0;
+/// How long spread shot and rapid fire last.
+pub const POWER_UP_TIME: f32 = 10.0;
+/// How much of the shields a shield pickup recharges.
+pub const SHIELD_RECHARGE: f32 = 0.25;
+
+/// The guns of the ship without power-ups.
+pub const PLAYER_GUNS: [Vec3; 2] = [Vec3::new(-1.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0)];
+const SPREAD_GUNS: [Vec3; 2] = [Vec3::new(-3.0, -0.5, 1.0), Vec3::new(3.0, -0.5, 1.0)];
+/// Rapid fire shortens the cooldown of every weapon by this factor.
+const RAPID_FIRE_FACTOR: f32 = 0.4;
+
+impl PowerUpKind {
+    fn random<R: Rng>(rng: &mut R) -> PowerUpKind {
+        match rng.gen_range(0..10) {
+            0..=3 => PowerUpKind::Shield,
+            4..=6 => PowerUpKind::SpreadShot,
+            7..=8 => PowerUpKind::RapidFire,
+            _ => PowerUpKind::Bomb,
+        }
+    }
+
+    fn color(self) -> Color {
+        Color::Srgba(match self {
+            PowerUpKind::Shield => DEEP_SKY_BLUE,
+            PowerUpKind::SpreadShot => GOLD,
+            PowerUpKind::RapidFire => MAGENTA,
+            PowerUpKind::Bomb => ORANGE,
+        })
+    }
+}
+
+pub fn drop_power_ups(
+    mut commands: Commands,
+    mut event_destroyed: EventReader<OpponentDestroyedEvent>,
+    mut handles: ResMut<SharedHandles>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut game_rng: ResMut<GameRng>,
+){
+    let rng = &mut game_rng.loot;
+    for event in event_destroyed.read() {
+        if rng.gen_bool(DROP_CHANCE) {
+            spawn_power_up(&mut commands, &mut handles, &mut materials, event.0, PowerUpKind::random(rng));
+        }
+    }
+}
+
+/// A pickup at `translation`, it is only a sensor so it passes through everything.
+pub fn spawn_power_up(
+    commands: &mut Commands,
+    handles: &mut SharedHandles,
+    materials: &mut Assets<StandardMaterial>,
+    translation: Vec3,
+    kind: PowerUpKind,
+) {
+    commands.spawn(PbrBundle {
+        mesh: handles.sphere.clone(),
+        material: handles.glowing_material(materials, kind.color()),
+        transform: Transform::from_translation(translation),
+        ..Default::default()
+    })
+        .insert(RigidBody::KinematicVelocityBased)
+        .insert(Velocity::default())
+        .insert(Collider::ball(1.5))
+        .insert(Sensor)
+        .insert(ActiveEvents::COLLISION_EVENTS)
+        // the ship is kinematic as well
+        .insert(ActiveCollisionTypes::default() | ActiveCollisionTypes::KINEMATIC_KINEMATIC)
+        .insert(Despawnable {
+            min: -1000.0,
+            max: 0.0
+        })
+        .insert(Name::new("PowerUp"))
+        .insert(PowerUp { kind })
+        .insert(StateScoped(InGame));
+}
+
+/// Pickups fly straight at the ship.
+pub fn attract_power_ups(
+    query_ship: Query<&Transform, With<Ship>>,
+    mut query: Query<(&Transform, &mut Velocity), With<PowerUp>>,
+){
+    let Ok(ship) = query_ship.get_single() else {
+        return;
+    };
+    for (transform, mut velocity) in query.iter_mut() {
+        velocity.linvel = (ship.translation - transform.translation).normalize_or_zero() * PICKUP_SPEED;
+    }
+}
+
+/// Counts down the timed power-ups and fits the ship's guns to what is still running.
+pub fn update_power_ups(
+    time: Res<Time>,
+    mut active: ResMut<ActivePowerUps>,
+    mut query: Query<&mut LaserGun, With<Ship>>,
+){
+    active.spread_shot = (active.spread_shot - time.delta_seconds()).max(0.0);
+    active.rapid_fire = (active.rapid_fire - time.delta_seconds()).max(0.0);
+
+    let Ok(mut gun) = query.get_single_mut() else {
+        return;
+    };
+    let guns = if active.spread_shot > 0.0 { 4 } else { 2 };
+    if gun.positions.len() != guns {
+        gun.positions = PLAYER_GUNS.to_vec();
+        if active.spread_shot > 0.0 {
+            gun.positions.extend(SPREAD_GUNS);
+        }
+    }
+    let cooldown = gun.weapon.spec().cooldown;
+    gun.std_cooldown = if active.rapid_fire > 0.0 { cooldown * RAPID_FIRE_FACTOR } else { cooldown };
+}
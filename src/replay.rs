@@ -0,0 +1,236 @@
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use bevy::prelude::*;
+use bevy::time::TimeUpdateStrategy;
+use bevy_rapier3d::prelude::*;
+use crate::game_state::{GameState, InGame};
+use crate::gameplay::PlayerInputSet;
+use crate::resources::{PlayerInput, StartLevel};
+use crate::rng::GameRng;
+
+const MAGIC: &[u8; 4] = b"PRRP";
+/// Version 2 added the weapon switch and version 3 the start level, older files are still read.
+const VERSION: u16 = 3;
+
+#[derive(Clone, Copy, Default, PartialEq, Debug)]
+pub struct ReplayFrame {
+    pub delta: f32,
+    pub input: PlayerInput,
+}
+
+/// A recorded session: the seed and start level plus the frame time and input of every running frame.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Replay {
+    pub seed: u64,
+    pub level: usize,
+    pub frames: Vec<ReplayFrame>,
+}
+
+impl Default for Replay {
+    fn default() -> Self {
+        Replay {
+            seed: 0,
+            level: 1,
+            frames: Vec::new(),
+        }
+    }
+}
+
+impl Replay {
+    pub fn load(path: &Path) -> io::Result<Replay> {
+        Replay::read(&mut BufReader::new(File::open(path)?))
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write(&mut writer)?;
+        writer.flush()
+    }
+
+    pub fn read(reader: &mut impl Read) -> io::Result<Replay> {
+        let mut magic = [0; 4];
+        reader.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "not a replay file"));
+        }
+        let version = u16::from_le_bytes(read_bytes(reader)?);
+        if version == 0 || version > VERSION {
+            return Err(io::Error::new(io::ErrorKind::InvalidData,
+                                      format!("unsupported replay version {version}")));
+        }
+        let seed = u64::from_le_bytes(read_bytes(reader)?);
+        let level = if version >= 3 { u32::from_le_bytes(read_bytes(reader)?) as usize } else { 1 };
+        let count = u32::from_le_bytes(read_bytes(reader)?);
+        let mut frames = Vec::with_capacity(count as usize);
+        for _ in 0..count {
+            let delta = f32::from_le_bytes(read_bytes(reader)?);
+            let horizontal = f32::from_le_bytes(read_bytes(reader)?);
+            let vertical = f32::from_le_bytes(read_bytes(reader)?);
+            let [fire] = read_bytes(reader)?;
+            let [next_weapon] = if version >= 2 { read_bytes(reader)? } else { [0] };
+            frames.push(ReplayFrame {
+                delta,
+                input: PlayerInput {
+                    horizontal,
+                    vertical,
+                    fire: fire != 0,
+                    next_weapon: next_weapon != 0,
+                },
+            });
+        }
+        Ok(Replay { seed, level, frames })
+    }
+
+    pub fn write(&self, writer: &mut impl Write) -> io::Result<()> {
+        writer.write_all(MAGIC)?;
+        writer.write_all(&VERSION.to_le_bytes())?;
+        writer.write_all(&self.seed.to_le_bytes())?;
+        writer.write_all(&(self.level as u32).to_le_bytes())?;
+        writer.write_all(&(self.frames.len() as u32).to_le_bytes())?;
+        for frame in &self.frames {
+            writer.write_all(&frame.delta.to_le_bytes())?;
+            writer.write_all(&frame.input.horizontal.to_le_bytes())?;
+            writer.write_all(&frame.input.vertical.to_le_bytes())?;
+            writer.write_all(&[frame.input.fire as u8])?;
+            writer.write_all(&[frame.input.next_weapon as u8])?;
+        }
+        Ok(())
+    }
+}
+
+fn read_bytes<const N: usize>(reader: &mut impl Read) -> io::Result<[u8; N]> {
+    let mut bytes = [0; N];
+    reader.read_exact(&mut bytes)?;
+    Ok(bytes)
+}
+
+/// Records the session to a file, or plays a recorded one back instead of the keyboard.
+pub enum ReplayPlugin {
+    Record(PathBuf),
+    Play(Replay),
+}
+
+#[derive(Resource)]
+pub struct ReplayRecorder {
+    pub path: PathBuf,
+    pub replay: Replay,
+}
+
+#[derive(Resource)]
+pub struct ReplayPlayer {
+    pub replay: Replay,
+    pub cursor: usize,
+}
+
+impl ReplayPlayer {
+    pub fn finished(&self) -> bool {
+        self.cursor >= self.replay.frames.len()
+    }
+}
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App){
+        match self {
+            ReplayPlugin::Record(path) => {
+                app
+                    .insert_resource(ReplayRecorder {
+                        path: path.clone(),
+                        replay: Replay::default(),
+                    })
+                    .add_systems(OnEnter(InGame), start_recording)
+                    .add_systems(Update, record_frame.in_set(PlayerInputSet::Override))
+                    .add_systems(OnEnter(GameState::End), save_recording)
+                    .add_systems(OnEnter(GameState::Won), save_recording)
+                    .add_systems(Last, save_recording.run_if(on_event::<AppExit>()));
+            }
+            ReplayPlugin::Play(replay) => {
+                let first_delta = replay.frames.first().map_or(0.0, |frame| frame.delta);
+                app
+                    .insert_resource(GameRng::new(replay.seed))
+                    .insert_resource(StartLevel(replay.level))
+                    .insert_resource(ReplayPlayer {
+                        replay: replay.clone(),
+                        cursor: 0,
+                    })
+                    // the time of the first running frame is already taken while loading
+                    .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(first_delta)))
+                    .add_systems(Startup, use_game_timestep)
+                    .add_systems(OnEnter(GameState::MainMenu), start_replay)
+                    .add_systems(Update, play_frame.in_set(PlayerInputSet::Override))
+                    .add_systems(Last, prepare_next_frame.run_if(in_state(GameState::Running)));
+            }
+        }
+    }
+}
+
+/// A replay holds one game, so a restart starts the recording over.
+fn start_recording(
+    game_rng: Res<GameRng>,
+    start_level: Res<StartLevel>,
+    mut recorder: ResMut<ReplayRecorder>
+){
+    recorder.replay = Replay {
+        seed: game_rng.seed,
+        level: start_level.0,
+        frames: Vec::new(),
+    };
+}
+
+fn record_frame(
+    time: Res<Time>,
+    player_input: Res<PlayerInput>,
+    mut recorder: ResMut<ReplayRecorder>
+){
+    recorder.replay.frames.push(ReplayFrame {
+        delta: time.delta_seconds(),
+        input: *player_input,
+    });
+}
+
+fn save_recording(
+    recorder: Res<ReplayRecorder>
+){
+    match recorder.replay.save(&recorder.path) {
+        Ok(()) => info!("replay saved to {}", recorder.path.display()),
+        Err(e) => error!("cannot save replay to {}: {e}", recorder.path.display()),
+    }
+}
+
+/// Replays are recorded with the game's variable timestep, so a headless simulation
+/// has to step Rapier the same way to reproduce them.
+fn use_game_timestep(
+    mut rapier_configuration: ResMut<RapierConfiguration>
+){
+    rapier_configuration.timestep_mode = RapierConfiguration::new(1.0).timestep_mode;
+}
+
+fn start_replay(
+    mut next_state: ResMut<NextState<GameState>>
+){
+    next_state.set(GameState::Running);
+}
+
+fn play_frame(
+    mut player: ResMut<ReplayPlayer>,
+    mut player_input: ResMut<PlayerInput>
+){
+    if let Some(frame) = player.replay.frames.get(player.cursor) {
+        *player_input = frame.input;
+        player.cursor += 1;
+        if player.finished() {
+            info!("replay finished after {} frames", player.cursor);
+        }
+    }
+}
+
+fn prepare_next_frame(
+    player: Res<ReplayPlayer>,
+    mut time_update_strategy: ResMut<TimeUpdateStrategy>
+){
+    *time_update_strategy = match player.replay.frames.get(player.cursor) {
+        Some(frame) => TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(frame.delta)),
+        None => TimeUpdateStrategy::Automatic,
+    };
+}
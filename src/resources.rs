@@ -1,7 +1,12 @@
 use bevy::prelude::*;
+use bevy::utils::HashMap;
 use bevy_asset_loader::prelude::*;
+use bevy_asset_loader::mapped::AssetFileName;
+use crate::audio::Synth;
+use crate::level::LevelDefinition;
+use crate::weapon::Weapon;
 
-#[derive(AssetCollection, Resource)]
+#[derive(AssetCollection, Resource, Default)]
 pub struct GameAssets {
     #[asset(path = "models/fighter.glb#Scene0")]
     pub fighter_scene: Handle<Scene>,
@@ -31,9 +36,29 @@ pub struct GameAssets {
     pub tile_7_texture: Handle<Image>,
     #[asset(path = "textures/tile08.png")]
     pub tile_8_texture: Handle<Image>,
+    #[asset(path = "levels", collection(typed))]
+    pub levels: Vec<Handle<LevelDefinition>>,
+    #[asset(path = "sounds/laser.sfx.ron")]
+    pub laser_sound: Handle<Synth>,
+    #[asset(path = "sounds/explosion.sfx.ron")]
+    pub explosion_sound: Handle<Synth>,
+    #[asset(path = "sounds/hit.sfx.ron")]
+    pub hit_sound: Handle<Synth>,
+    /// by file name, see [`LevelDefinition::music`]
+    #[asset(path = "sounds/music", collection(typed, mapped))]
+    pub music: HashMap<AssetFileName, Handle<Synth>>,
 }
 
-#[derive(Resource)]
+impl GameAssets {
+    pub fn level<'a>(&self, definitions: &'a Assets<LevelDefinition>, level: usize) -> Option<&'a LevelDefinition> {
+        self.levels
+            .iter()
+            .filter_map(|handle| definitions.get(handle))
+            .find(|definition| definition.level == level)
+    }
+}
+
+#[derive(Resource, Debug)]
 pub struct Level {
     pub value: usize,
 }
@@ -44,11 +69,120 @@ impl Default for Level {
     }
 }
 
-#[derive(Resource)]
+/// The weapons the player switches between. A new game only has the laser,
+/// the levels unlock the others (see [`crate::level::LevelDefinition::unlocks`]).
+#[derive(Resource, Clone, PartialEq, Debug)]
+pub struct UnlockedWeapons(pub Vec<Weapon>);
+
+impl Default for UnlockedWeapons {
+    fn default() -> Self {
+        Self(vec![Weapon::Laser])
+    }
+}
+
+impl UnlockedWeapons {
+    pub fn unlock(&mut self, weapon: Weapon) {
+        if !self.0.contains(&weapon) {
+            self.0.push(weapon);
+        }
+    }
+
+    /// The next unlocked weapon after `weapon` in the order of [`Weapon::ALL`].
+    pub fn next(&self, weapon: Weapon) -> Weapon {
+        let index = Weapon::ALL.iter().position(|w| *w == weapon).unwrap_or(0);
+        (1..=Weapon::ALL.len())
+            .map(|step| Weapon::ALL[(index + step) % Weapon::ALL.len()])
+            .find(|weapon| self.0.contains(weapon))
+            .unwrap_or(weapon)
+    }
+}
+
+/// The level every new game starts at.
+#[derive(Resource, Debug)]
+pub struct StartLevel(pub usize);
+
+impl Default for StartLevel {
+    fn default() -> Self {
+        Self(1)
+    }
+}
+
+#[derive(Resource, Debug)]
 pub struct SpawnTimer(pub Timer);
 
 impl Default for SpawnTimer {
     fn default() -> Self {
         Self(Timer::from_seconds(2.0, TimerMode::Repeating))
     }
-}
\ No newline at end of file
+}
+/// Counted during a run and shown when the campaign is won.
+#[derive(Resource, Default)]
+pub struct GameStats {
+    pub kills: u32,
+    pub shots: u32,
+    pub shots_hit: u32,
+    pub time: f32,
+}
+
+impl GameStats {
+    pub fn accuracy(&self) -> f32 {
+        if self.shots == 0 {
+            0.0
+        } else {
+            self.shots_hit as f32 / self.shots as f32
+        }
+    }
+}
+
+/// Where the current level is with its boss.
+#[derive(Resource, Default, Clone, Copy, PartialEq, Debug)]
+pub enum BossFight {
+    #[default]
+    None,
+    Fighting,
+    Defeated,
+}
+
+const KILLS_PER_MULTIPLIER: u32 = 5;
+const MAX_MULTIPLIER: u32 = 8;
+
+/// Points for destroyed opponents. Kills in a row raise the multiplier,
+/// a hit by an opponent's laser resets it.
+#[derive(Resource, Default)]
+pub struct Score {
+    pub points: u32,
+    pub streak: u32,
+}
+
+impl Score {
+    pub fn multiplier(&self) -> u32 {
+        (1 + self.streak / KILLS_PER_MULTIPLIER).min(MAX_MULTIPLIER)
+    }
+
+    pub fn add_kill(&mut self, points: u32) {
+        self.points += points * self.multiplier();
+        self.streak += 1;
+    }
+
+    pub fn break_streak(&mut self) {
+        self.streak = 0;
+    }
+}
+
+/// Seconds left on the timed power-ups, nothing is running at zero.
+#[derive(Resource, Default, Clone, Copy, PartialEq, Debug)]
+pub struct ActivePowerUps {
+    pub spread_shot: f32,
+    pub rapid_fire: f32,
+}
+
+/// What the player wants to do this frame, read from the input actions or from a replay.
+/// The axes are analog, a half tilted stick moves the ship at half speed.
+#[derive(Resource, Default, Clone, Copy, PartialEq, Debug)]
+pub struct PlayerInput {
+    pub horizontal: f32,
+    pub vertical: f32,
+    pub fire: bool,
+    /// switch to the next weapon this frame
+    pub next_weapon: bool,
+}
@@ -0,0 +1,45 @@
+use bevy::prelude::*;
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+/// All gameplay randomness comes from here so a run can be replayed from its seed.
+/// Every consumer has its own stream, so adding an effect does not move the opponents.
+#[derive(Resource)]
+pub struct GameRng {
+    pub seed: u64,
+    pub spawn: ChaCha8Rng,
+    pub ai: ChaCha8Rng,
+    pub effects: ChaCha8Rng,
+    pub loot: ChaCha8Rng,
+    pub asteroids: ChaCha8Rng,
+}
+
+impl GameRng {
+    pub fn new(seed: u64) -> Self {
+        GameRng {
+            seed,
+            spawn: stream(seed, 1),
+            ai: stream(seed, 2),
+            effects: stream(seed, 3),
+            loot: stream(seed, 4),
+            asteroids: stream(seed, 5),
+        }
+    }
+
+    /// An independent generator derived from the seed, for new consumers.
+    pub fn fork(&self, stream_id: u64) -> ChaCha8Rng {
+        stream(self.seed, stream_id)
+    }
+}
+
+impl Default for GameRng {
+    fn default() -> Self {
+        Self::new(rand::random())
+    }
+}
+
+fn stream(seed: u64, stream_id: u64) -> ChaCha8Rng {
+    let mut rng = ChaCha8Rng::seed_from_u64(seed);
+    rng.set_stream(stream_id);
+    rng
+}
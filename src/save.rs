@@ -0,0 +1,204 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use crate::components::{Health, Ship};
+use crate::events::LevelChangedEvent;
+use crate::game_state::{GameState, InGame};
+use crate::gameplay::{unlock_weapons, LevelEntry, SetupSet};
+use crate::replay::ReplayPlayer;
+use crate::resources::{Score, StartLevel, UnlockedWeapons};
+use crate::weapon::Weapon;
+
+/// The version written into every save, raise it when [`SaveGame`] changes.
+pub const SAVE_VERSION: u32 = 1;
+pub const SLOT_COUNT: usize = 3;
+
+/// The campaign progress at the start of a level.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct SaveGame {
+    pub version: u32,
+    pub level: usize,
+    /// the ship's health, 1 is full
+    pub shields: f32,
+    pub score: u32,
+    pub weapons: Vec<Weapon>,
+}
+
+/// Only the version, read first so a save of another version is refused instead of misread.
+#[derive(Deserialize)]
+struct SaveHeader {
+    version: u32,
+}
+
+impl SaveGame {
+    /// `<data dir>/planet-rust/saves`, if the platform has a data directory.
+    pub fn default_dir() -> Option<PathBuf> {
+        dirs::data_dir().map(|dir| dir.join("planet-rust").join("saves"))
+    }
+
+    /// `slot1.ron` for the first slot.
+    pub fn path(dir: &Path, slot: usize) -> PathBuf {
+        dir.join(format!("slot{}.ron", slot + 1))
+    }
+
+    pub fn load(path: &Path) -> io::Result<SaveGame> {
+        let text = fs::read_to_string(path)?;
+        let header: SaveHeader = ron::from_str(&text).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        if header.version != SAVE_VERSION {
+            return Err(io::Error::new(io::ErrorKind::InvalidData,
+                                      format!("save version {}, expected {SAVE_VERSION}", header.version)));
+        }
+        ron::from_str(&text).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let text = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        fs::write(path, text)
+    }
+}
+
+/// The saved games and the slot the running game is saved to.
+#[derive(Resource, Debug)]
+pub struct SaveSlots {
+    /// where the slots are written, nowhere if `None`
+    pub dir: Option<PathBuf>,
+    pub slots: Vec<Option<SaveGame>>,
+    pub active: usize,
+}
+
+impl SaveSlots {
+    /// Reads every slot, a missing or unreadable one is empty.
+    pub fn load(dir: Option<PathBuf>) -> SaveSlots {
+        let slots = (0..SLOT_COUNT).map(|slot| {
+            let path = SaveGame::path(dir.as_ref()?, slot);
+            match SaveGame::load(&path) {
+                Ok(save) => Some(save),
+                Err(e) if e.kind() == io::ErrorKind::NotFound => None,
+                Err(e) => {
+                    warn!("cannot read saved game {}: {e}", path.display());
+                    None
+                }
+            }
+        }).collect();
+        SaveSlots {
+            dir,
+            slots,
+            active: 0,
+        }
+    }
+}
+
+/// Keeps the saved games as they are, inserted by the debug console whose commands
+/// (e.g. `level`) would otherwise be saved as progress.
+#[derive(Resource, Default, Debug)]
+pub struct SavingDisabled;
+
+/// The saved game the next game continues, taken when it starts.
+#[derive(Resource, Default, Debug)]
+pub struct Resume(pub Option<SaveGame>);
+
+/// Saves the campaign to the active slot whenever a level is reached and empties it once
+/// the campaign is won, and continues the game in [`Resume`]. A replay, a game started at a
+/// later level and one with [`SavingDisabled`] leave the slots alone. The binary passes [`SaveGame::default_dir`], without a `dir`
+/// the slots are only kept in memory.
+#[derive(Default)]
+pub struct SavePlugin {
+    pub dir: Option<PathBuf>,
+}
+
+impl Plugin for SavePlugin {
+    fn build(&self, app: &mut App){
+        app
+            .insert_resource(SaveSlots::load(self.dir.clone()))
+            .init_resource::<Resume>()
+            .add_systems(OnEnter(InGame), resume_game.after(SetupSet))
+            .add_systems(OnEnter(GameState::Won), clear_finished_game.run_if(saving_enabled))
+            // after the weapons of the new level are handed out
+            .add_systems(Update, save_progress.after(unlock_weapons).run_if(in_state(InGame).and_then(saving_enabled)));
+    }
+}
+
+/// Only the player's own campaign is saved.
+fn saving_enabled(
+    start_level: Res<StartLevel>,
+    replay_player: Option<Res<ReplayPlayer>>,
+    saving_disabled: Option<Res<SavingDisabled>>,
+) -> bool {
+    start_level.0 == 1 && replay_player.is_none() && saving_disabled.is_none()
+}
+
+fn resume_game(
+    mut resume: ResMut<Resume>,
+    mut level_entry: LevelEntry,
+    mut score: ResMut<Score>,
+    mut unlocked_weapons: ResMut<UnlockedWeapons>,
+    mut query_ship: Query<(&mut Ship, &mut Health)>,
+){
+    let Some(save) = resume.0.take() else {
+        return;
+    };
+    let (mut ship, mut health) = query_ship.single_mut();
+    // the first level is set up already
+    if save.level > level_entry.level() {
+        match level_entry.enter(save.level) {
+            Some(hits) => ship.hits = hits,
+            None => warn!("there is no level {} to continue, starting at level {}", save.level, level_entry.level()),
+        }
+    }
+    health.current = health.max * save.shields.clamp(0.0, 1.0);
+    score.points = save.score;
+    unlocked_weapons.0 = save.weapons;
+    info!("continuing at level {}", level_entry.level());
+}
+
+fn save_progress(
+    mut event_level_changed: EventReader<LevelChangedEvent>,
+    score: Res<Score>,
+    unlocked_weapons: Res<UnlockedWeapons>,
+    mut save_slots: ResMut<SaveSlots>,
+    query_ship: Query<&Health, With<Ship>>,
+){
+    let Some(LevelChangedEvent(level)) = event_level_changed.read().last() else {
+        return;
+    };
+    let Ok(health) = query_ship.get_single() else {
+        return;
+    };
+    let save = SaveGame {
+        version: SAVE_VERSION,
+        level: *level,
+        shields: health.fraction(),
+        score: score.points,
+        weapons: unlocked_weapons.0.clone(),
+    };
+    let slot = save_slots.active;
+    if let Some(dir) = &save_slots.dir {
+        let path = SaveGame::path(dir, slot);
+        if let Err(e) = save.save(&path) {
+            error!("cannot save the game to {}: {e}", path.display());
+        }
+    }
+    save_slots.slots[slot] = Some(save);
+}
+
+/// A won campaign has nothing left to continue.
+fn clear_finished_game(
+    mut save_slots: ResMut<SaveSlots>,
+){
+    let slot = save_slots.active;
+    if let Some(dir) = &save_slots.dir {
+        let path = SaveGame::path(dir, slot);
+        match fs::remove_file(&path) {
+            Ok(()) => {}
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+            Err(e) => error!("cannot remove the saved game {}: {e}", path.display()),
+        }
+    }
+    save_slots.slots[slot] = None;
+}
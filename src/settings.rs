@@ -0,0 +1,186 @@
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use bevy::prelude::*;
+use bevy::window::{PresentMode, PrimaryWindow, WindowMode, WindowResolution};
+use serde::{Deserialize, Serialize};
+
+/// Size of the window in pixels, written `1280x720`.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Resolution {
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Resolution {
+    pub const DEFAULT: Resolution = Resolution { width: 920, height: 640 };
+    /// offered in the options menu
+    pub const PRESETS: [Resolution; 4] = [Resolution::DEFAULT,
+                                          Resolution { width: 1280, height: 720 },
+                                          Resolution { width: 1600, height: 900 },
+                                          Resolution { width: 1920, height: 1080 }];
+}
+
+impl FromStr for Resolution {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (width, height) = s.split_once('x').ok_or("expected WIDTHxHEIGHT, e.g. 1280x720")?;
+        let parse = |side: &str| side.trim().parse::<u32>().ok().filter(|side| *side > 0)
+            .ok_or_else(|| format!("invalid size {side}"));
+        Ok(Resolution {
+            width: parse(width)?,
+            height: parse(height)?,
+        })
+    }
+}
+
+impl fmt::Display for Resolution {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}x{}", self.width, self.height)
+    }
+}
+
+/// Multisample anti-aliasing, [`Msaa`] itself cannot be saved.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Antialiasing {
+    Off,
+    X2,
+    X4,
+    X8,
+}
+
+impl Antialiasing {
+    pub const ALL: [Antialiasing; 4] = [Antialiasing::Off, Antialiasing::X2, Antialiasing::X4, Antialiasing::X8];
+
+    pub fn msaa(self) -> Msaa {
+        match self {
+            Antialiasing::Off => Msaa::Off,
+            Antialiasing::X2 => Msaa::Sample2,
+            Antialiasing::X4 => Msaa::Sample4,
+            Antialiasing::X8 => Msaa::Sample8,
+        }
+    }
+}
+
+/// Window and graphics options, kept in the user's config directory. The volumes and the
+/// key bindings have their own files, see [`crate::audio::AudioSettings`] and
+/// [`crate::actions::InputBindings`].
+#[derive(Resource, Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+#[serde(default)]
+pub struct Settings {
+    /// size of the window, fullscreen uses the whole screen
+    pub resolution: Resolution,
+    pub fullscreen: bool,
+    pub vsync: bool,
+    pub antialiasing: Antialiasing,
+    pub shadows: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            resolution: Resolution::DEFAULT,
+            fullscreen: false,
+            vsync: true,
+            antialiasing: Antialiasing::X4,
+            shadows: true,
+        }
+    }
+}
+
+impl Settings {
+    /// `<config dir>/planet-rust/settings.ron`, if the platform has a config directory.
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("planet-rust").join("settings.ron"))
+    }
+
+    pub fn load(path: &Path) -> io::Result<Settings> {
+        let text = fs::read_to_string(path)?;
+        ron::from_str(&text).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let text = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        fs::write(path, text)
+    }
+
+    /// The settings from the config file, or the defaults if there is none.
+    pub fn load_or_default() -> Settings {
+        let Some(path) = Settings::default_path() else {
+            return Settings::default();
+        };
+        match Settings::load(&path) {
+            Ok(settings) => settings,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Settings::default(),
+            Err(e) => {
+                warn!("cannot read settings from {}: {e}", path.display());
+                Settings::default()
+            }
+        }
+    }
+
+    /// The primary window, created before the settings can be applied at runtime.
+    pub fn window(&self) -> Window {
+        let mut window = Window {
+            title: "planet rust".to_string(),
+            resolution: WindowResolution::new(self.resolution.width as f32, self.resolution.height as f32),
+            resizable: false,
+            ..default()
+        };
+        self.apply_to_window(&mut window);
+        window
+    }
+
+    fn apply_to_window(&self, window: &mut Window) {
+        window.resolution.set(self.resolution.width as f32, self.resolution.height as f32);
+        window.mode = if self.fullscreen { WindowMode::BorderlessFullscreen } else { WindowMode::Windowed };
+        window.present_mode = if self.vsync { PresentMode::AutoVsync } else { PresentMode::AutoNoVsync };
+    }
+}
+
+/// Applies [`Settings`] whenever they change: window, MSAA and the shadows of every light.
+/// The settings are inserted by the binary, which reads them before the window exists and
+/// builds the window and MSAA from them, so they are only applied once they are edited.
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App){
+        app
+            .init_resource::<Settings>()
+            .init_resource::<Msaa>()
+            .add_systems(Update, (apply_settings.run_if(resource_changed::<Settings>
+                .and_then(not(resource_added::<Settings>))), apply_shadows));
+    }
+}
+
+fn apply_settings(
+    settings: Res<Settings>,
+    mut msaa: ResMut<Msaa>,
+    mut query_window: Query<&mut Window, With<PrimaryWindow>>,
+    mut query_light: Query<&mut DirectionalLight>,
+){
+    *msaa = settings.antialiasing.msaa();
+    if let Ok(mut window) = query_window.get_single_mut() {
+        settings.apply_to_window(&mut window);
+    }
+    for mut light in query_light.iter_mut() {
+        light.shadows_enabled = settings.shadows;
+    }
+}
+
+/// Lights come and go with the game.
+fn apply_shadows(
+    settings: Res<Settings>,
+    mut query_light: Query<&mut DirectionalLight, Added<DirectionalLight>>,
+){
+    for mut light in query_light.iter_mut() {
+        light.shadows_enabled = settings.shadows;
+    }
+}
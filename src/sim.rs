@@ -0,0 +1,212 @@
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+use bevy::prelude::*;
+use bevy::app::Plugins;
+use bevy::asset::io::file::FileAssetReader;
+use bevy::input::ButtonState;
+use bevy::input::keyboard::{Key, KeyboardInput, NativeKey};
+use bevy::input::InputPlugin;
+use bevy::scene::ScenePlugin;
+use bevy::state::app::StatesPlugin;
+use bevy::time::TimeUpdateStrategy;
+use bevy_rapier3d::prelude::*;
+use crate::audio::{AudioBackend, GameAudioPlugin};
+use crate::components::{Boss, Health, Opponent, Ship};
+use crate::game_state::GameState;
+use crate::gameplay::GameplayPlugin;
+use crate::level::LevelDefinition;
+use crate::resources::{GameAssets, Level, StartLevel};
+use crate::rng::GameRng;
+
+/// Runs the gameplay without a window, renderer or sound: `MinimalPlugins`, Rapier with a
+/// fixed timestep, the null audio backend and the level definitions read straight from disk.
+pub struct GameSimPlugin {
+    pub timestep: f32,
+    pub levels_path: PathBuf,
+    pub seed: u64,
+    /// the level the game starts at
+    pub level: usize,
+}
+
+impl Default for GameSimPlugin {
+    fn default() -> Self {
+        GameSimPlugin {
+            timestep: 1.0 / 60.0,
+            levels_path: FileAssetReader::get_base_path().join("assets/levels"),
+            seed: 0,
+            level: 1,
+        }
+    }
+}
+
+impl Plugin for GameSimPlugin {
+    fn build(&self, app: &mut App){
+        app
+            .add_plugins((MinimalPlugins, TransformPlugin, HierarchyPlugin, InputPlugin,
+                          AssetPlugin::default(), ScenePlugin, StatesPlugin))
+            .init_asset::<Mesh>()
+            .init_asset::<StandardMaterial>()
+            .init_asset::<Image>()
+            .init_asset::<LevelDefinition>()
+            .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(self.timestep)))
+            .insert_resource(RapierConfiguration {
+                timestep_mode: TimestepMode::Fixed {
+                    dt: self.timestep,
+                    substeps: 1,
+                },
+                ..RapierConfiguration::new(1.0)
+            })
+            .insert_resource(GameRng::new(self.seed))
+            .insert_resource(StartLevel(self.level))
+            .add_plugins((RapierPhysicsPlugin::<NoUserData>::default(), GameplayPlugin,
+                          GameAudioPlugin { backend: AudioBackend::Null }))
+            .insert_state(GameState::Loading);
+
+        let game_assets = load_levels(app, &self.levels_path);
+        app.insert_resource(game_assets);
+    }
+}
+
+fn load_levels(app: &mut App, path: &PathBuf) -> GameAssets {
+    let mut definitions = app.world_mut().resource_mut::<Assets<LevelDefinition>>();
+    let mut levels = Vec::new();
+    let entries = fs::read_dir(path)
+        .unwrap_or_else(|e| panic!("cannot read levels from {}: {e}", path.display()));
+    for entry in entries {
+        let file = entry.unwrap().path();
+        if file.to_string_lossy().ends_with(".level.ron") {
+            let text = fs::read_to_string(&file).unwrap();
+            let definition: LevelDefinition = ron::from_str(&text)
+                .unwrap_or_else(|e| panic!("invalid level {}: {e}", file.display()));
+            levels.push(definitions.add(definition));
+        }
+    }
+    GameAssets {
+        levels,
+        ..default()
+    }
+}
+
+/// A headless game that tests can step frame by frame while holding keys.
+pub struct GameSim {
+    pub app: App,
+}
+
+impl Default for GameSim {
+    fn default() -> Self {
+        Self::new(GameSimPlugin::default())
+    }
+}
+
+impl GameSim {
+    pub fn new(plugin: GameSimPlugin) -> Self {
+        Self::with_plugins(plugin, ())
+    }
+
+    pub fn with_plugins<M>(plugin: GameSimPlugin, plugins: impl Plugins<M>) -> Self {
+        let mut app = App::new();
+        app.add_plugins(plugin).add_plugins(plugins);
+        // the very first update has no frame time, so it stands in for loading
+        app.update();
+        app.world_mut().resource_mut::<NextState<GameState>>().set(GameState::Running);
+        // enter `GameState::Running`, spawn the ship and play the first frame
+        app.update();
+        GameSim { app }
+    }
+
+    /// Sends the key event like a window would, so the key is also `just_pressed`
+    /// in the next frame.
+    pub fn press(&mut self, key: KeyCode) {
+        self.send_key(key, ButtonState::Pressed);
+    }
+
+    pub fn release(&mut self, key: KeyCode) {
+        self.send_key(key, ButtonState::Released);
+    }
+
+    fn send_key(&mut self, key_code: KeyCode, state: ButtonState) {
+        self.app.world_mut().send_event(KeyboardInput {
+            key_code,
+            logical_key: Key::Unidentified(NativeKey::Unidentified),
+            state,
+            window: Entity::PLACEHOLDER,
+        });
+    }
+
+    pub fn run(&mut self, frames: usize) {
+        for _ in 0..frames {
+            self.app.update();
+        }
+    }
+
+    pub fn ship(&mut self) -> Mut<'_, Ship> {
+        let world = self.app.world_mut();
+        let entity = world.query_filtered::<Entity, With<Ship>>().single(world);
+        world.get_mut::<Ship>(entity).unwrap()
+    }
+
+    /// The ship's shields.
+    pub fn ship_health(&mut self) -> Mut<'_, Health> {
+        let world = self.app.world_mut();
+        let entity = world.query_filtered::<Entity, With<Ship>>().single(world);
+        world.get_mut::<Health>(entity).unwrap()
+    }
+
+    pub fn ship_transform(&mut self) -> Transform {
+        let world = self.app.world_mut();
+        *world.query_filtered::<&Transform, With<Ship>>().single(world)
+    }
+
+    /// An opponent `offset` from the ship that neither moves nor shoots,
+    /// tests add what else they need (e.g. a `Velocity` or `Damage`) to it.
+    pub fn spawn_opponent(&mut self, offset: Vec3, health: f32) -> Entity {
+        let ship = self.ship_transform().translation;
+        self.app.world_mut().spawn((
+            TransformBundle::from_transform(Transform::from_translation(ship + offset)),
+            RigidBody::Dynamic,
+            Collider::cuboid(3.0, 3.0, 3.0),
+            ActiveEvents::COLLISION_EVENTS,
+            GravityScale(0.0),
+            Opponent { points: 100 },
+            Health::new(health),
+        )).id()
+    }
+
+    pub fn count<T: Component>(&mut self) -> usize {
+        let world = self.app.world_mut();
+        world.query::<&T>().iter(world).count()
+    }
+
+    /// Wears the boss down to nothing, it is destroyed in the next frame.
+    pub fn defeat_boss(&mut self) {
+        let world = self.app.world_mut();
+        for mut health in world.query_filtered::<&mut Health, With<Boss>>().iter_mut(world) {
+            health.current = 0.0;
+        }
+    }
+
+    /// Reaches the level's hits and destroys its boss, if it has one.
+    pub fn finish_level(&mut self) {
+        self.ship().hits = 0;
+        self.run(1);
+        if self.count::<Boss>() > 0 {
+            self.defeat_boss();
+            self.run(1);
+        }
+    }
+
+    pub fn level(&self) -> usize {
+        self.app.world().resource::<Level>().value
+    }
+
+    /// Switches the state and runs the frame that applies it.
+    pub fn set_state(&mut self, state: GameState) {
+        self.app.world_mut().resource_mut::<NextState<GameState>>().set(state);
+        self.app.update();
+    }
+
+    pub fn state(&self) -> GameState {
+        *self.app.world().resource::<State<GameState>>().get()
+    }
+}
@@ -1,13 +1,23 @@
 use bevy::prelude::*;
 use std::f32::consts::PI;
+use crate::game_state::GameState;
 
 pub struct SkyboxPlugin;
 
+/// Turns a quarter for every [`RotateSkyboxEvent`], slowly and only while the game runs.
 #[derive(Component)]
-struct Skybox{
+pub struct Skybox{
     rotate:f32
 }
 
+impl Skybox {
+    /// Back to how a new game starts.
+    pub fn reset(&mut self, transform: &mut Transform) {
+        self.rotate = 0.0;
+        transform.rotation = Quat::IDENTITY;
+    }
+}
+
 #[derive(Component)]
 struct Wall{}
 
@@ -19,7 +29,7 @@ impl Plugin for SkyboxPlugin {
         app
             .add_systems(PreStartup,setup_skybox)
             .add_event::<RotateSkyboxEvent>()
-            .add_systems(Update, (start_rotate, rotate));
+            .add_systems(Update, (start_rotate, rotate.run_if(in_state(GameState::Running))));
     }
 }
 
@@ -31,20 +41,20 @@ fn setup_skybox(
     mut materials: ResMut<Assets<StandardMaterial>>,
     asset_server: Res<AssetServer>,
 ){
-    let images = vec!["images/skybox_front.png",
+    let images = ["images/skybox_front.png",
                       "images/skybox_left.png",
                       "images/skybox_right.png",
                       "images/skybox_back.png",
                       "images/skybox_down.png",
                       "images/skybox_up.png"];
     let distance = SIZE/2.0;
-    let translations = vec![Vec3::new(0.0, 0.0, -distance),
+    let translations = [Vec3::new(0.0, 0.0, -distance),
                             Vec3::new(distance, 0.0, 0.0),
                             Vec3::new(-distance, 0.0, 0.0),
                             Vec3::new(0.0, 0.0, distance),
                             Vec3::new(0.0, -distance, 0.0),
                             Vec3::new(0.0, distance, 0.0),];
-    let rotations =vec![ Quat::from_rotation_x(0.0),
+    let rotations =[ Quat::from_rotation_x(0.0),
                          Quat::from_euler(EulerRot::XYZ,0.0,-PI/2.0,0.0),
                          Quat::from_euler(EulerRot::XYZ,0.0,PI/2.0,0.0),
                          Quat::from_euler(EulerRot::XYZ,PI,0.0,-PI),
@@ -106,7 +116,7 @@ fn start_rotate(
 ){
     for _ in rotate_events.read(){
         for mut skybox in query.iter_mut(){
-            skybox.rotate = PI/2.0;
+            skybox.rotate += PI/2.0;
         }
     }
 }
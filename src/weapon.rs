@@ -0,0 +1,223 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use serde::{Deserialize, Serialize};
+use crate::components::{Boss, Damage, Despawnable, Homing, Laser, Opponent, Ship};
+use crate::pool::{EntityPool, PoolKind, SharedHandles};
+
+/// How long the charged beam needs to reach full power.
+pub const CHARGE_TIME: f32 = 1.5;
+const SCATTER_PELLETS: usize = 5;
+/// Angle between the outermost pellets of a scatter shot, in degrees.
+const SCATTER_ANGLE: f32 = 20.0;
+
+/// What a gun fires. The player switches between the unlocked ones, see
+/// [`crate::resources::UnlockedWeapons`], opponents carry the one from their spawn entry.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum Weapon {
+    #[default]
+    Laser,
+    /// slow, but turns towards the nearest target
+    HomingMissile,
+    /// charges while the trigger is held and fires on release, stronger the longer it charged
+    ChargedBeam,
+    /// a fan of short range pellets
+    Scatter,
+}
+
+/// The shape of a projectile, it is both its mesh and its collider.
+#[derive(Clone, Copy, Debug)]
+pub enum ProjectileShape {
+    Box(Vec3),
+    Ball(f32),
+}
+
+/// Everything a weapon's projectiles are made of.
+#[derive(Clone, Copy, Debug)]
+pub struct WeaponSpec {
+    pub shape: ProjectileShape,
+    pub speed: f32,
+    pub damage: f32,
+    /// seconds between two shots
+    pub cooldown: f32,
+    /// what a projectile weighs, it pushes what it hits with its momentum
+    pub mass: f32,
+}
+
+impl Weapon {
+    pub const ALL: [Weapon; 4] = [Weapon::Laser, Weapon::HomingMissile, Weapon::ChargedBeam, Weapon::Scatter];
+
+    pub fn spec(self) -> WeaponSpec {
+        match self {
+            Weapon::Laser => WeaponSpec {
+                shape: ProjectileShape::Box(Vec3::new(0.2, 0.2, 3.2)),
+                speed: 600.0,
+                damage: 1.0,
+                cooldown: 0.2,
+                mass: 0.05,
+            },
+            Weapon::HomingMissile => WeaponSpec {
+                shape: ProjectileShape::Box(Vec3::new(0.5, 0.5, 2.0)),
+                speed: 150.0,
+                damage: 2.0,
+                cooldown: 0.6,
+                mass: 0.5,
+            },
+            // a full charge does five times the damage, the beam gets thicker with it
+            Weapon::ChargedBeam => WeaponSpec {
+                shape: ProjectileShape::Box(Vec3::new(0.3, 0.3, 8.0)),
+                speed: 400.0,
+                damage: 1.0,
+                cooldown: 0.3,
+                mass: 0.2,
+            },
+            Weapon::Scatter => WeaponSpec {
+                shape: ProjectileShape::Ball(0.4),
+                speed: 450.0,
+                damage: 1.0,
+                cooldown: 0.5,
+                mass: 0.03,
+            },
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Weapon::Laser => "Laser",
+            Weapon::HomingMissile => "Missile",
+            Weapon::ChargedBeam => "Beam",
+            Weapon::Scatter => "Scatter",
+        }
+    }
+}
+
+/// Fires one shot of `weapon` from `translation`, `charge` from 0 to 1 only matters for the beam.
+/// Returns how many projectiles left the gun.
+pub fn fire_weapon(
+    commands: &mut Commands,
+    pool: &mut EntityPool,
+    handles: &mut SharedHandles,
+    materials: &mut Assets<StandardMaterial>,
+    weapon: Weapon,
+    charge: f32,
+    translation: Vec3,
+    direction: Vec3,
+    color: Color,
+    player: bool,
+) -> u32 {
+    let spec = weapon.spec();
+    match weapon {
+        Weapon::Laser => {
+            spawn_projectile(commands, pool, handles, materials, spec, translation, direction, color, player);
+        }
+        Weapon::HomingMissile => {
+            let missile = spawn_projectile(commands, pool, handles, materials, spec, translation, direction, color, player);
+            commands.entity(missile).try_insert(Homing { turn_rate: 4.0 });
+        }
+        Weapon::ChargedBeam => {
+            let charge = charge.clamp(0.0, 1.0);
+            let ProjectileShape::Box(size) = spec.shape else {
+                unreachable!("the beam is a box");
+            };
+            let spec = WeaponSpec {
+                shape: ProjectileShape::Box(size * Vec3::new(1.0 + 2.0 * charge, 1.0 + 2.0 * charge, 1.0)),
+                damage: spec.damage + (charge * 4.0).round(),
+                ..spec
+            };
+            spawn_projectile(commands, pool, handles, materials, spec, translation, direction, color, player);
+        }
+        Weapon::Scatter => {
+            // the fan spreads sideways, as seen from the gun
+            let side = direction.cross(Vec3::Y).try_normalize().unwrap_or(direction.any_orthonormal_vector());
+            let up = side.cross(direction);
+            for pellet in 0..SCATTER_PELLETS {
+                let t = pellet as f32 / (SCATTER_PELLETS - 1) as f32 - 0.5;
+                // alternate above and below, so the fan is not flat
+                let tilt = if pellet % 2 == 0 { 0.25 } else { -0.25 };
+                let rotation = Quat::from_axis_angle(up, (SCATTER_ANGLE * t).to_radians())
+                    * Quat::from_axis_angle(side, (SCATTER_ANGLE * tilt * 0.5).to_radians());
+                spawn_projectile(commands, pool, handles, materials, spec, translation, rotation * direction, color, player);
+            }
+            return SCATTER_PELLETS as u32;
+        }
+    }
+    1
+}
+
+fn spawn_projectile(
+    commands: &mut Commands,
+    pool: &mut EntityPool,
+    handles: &mut SharedHandles,
+    materials: &mut Assets<StandardMaterial>,
+    spec: WeaponSpec,
+    translation: Vec3,
+    direction: Vec3,
+    color: Color,
+    player: bool,
+) -> Entity {
+    // the shared meshes and colliders have unit size, the scale makes them as big as the shot
+    let (mesh, collider, scale) = match spec.shape {
+        ProjectileShape::Box(size) => (handles.cube.clone(), Collider::cuboid(0.5, 0.5, 0.5), size),
+        ProjectileShape::Ball(radius) => (handles.sphere.clone(), Collider::ball(1.0), Vec3::splat(radius)),
+    };
+    let projectile = pool.acquire(commands, PoolKind::Laser);
+    commands.entity(projectile).insert(PbrBundle {
+        mesh,
+        material: handles.glowing_material(materials, color),
+        transform: Transform::from_translation(translation).looking_to(direction, Vec3::Y).with_scale(scale),
+        ..Default::default()
+    })
+        .insert(RigidBody::KinematicVelocityBased)
+        .insert(Sleeping::disabled())
+        .insert(collider)
+        // only reports what it hits, the push comes from its momentum (see `collision::push`)
+        .insert(Sensor)
+        .insert(Velocity {
+            linvel: direction * spec.speed,
+            ..Default::default()
+        })
+        .insert(GravityScale(0.0))
+        .insert(Despawnable {
+            min: -1000.0,
+            max: 0.0
+        })
+        .insert(Name::new("Laser"))
+        .insert(Laser {
+            player,
+            mass: spec.mass,
+        })
+        .insert(Damage {
+            amount: spec.damage,
+        });
+    projectile
+}
+
+/// Missiles of the player turn towards the nearest opponent ahead of them,
+/// those of opponents towards the ship.
+pub fn steer_missiles(
+    time: Res<Time>,
+    query_ship: Query<&Transform, (With<Ship>, Without<Laser>)>,
+    query_target: Query<&Transform, (Or<(With<Opponent>, With<Boss>)>, Without<Laser>)>,
+    mut query: Query<(&mut Transform, &mut Velocity, &Homing, &Laser)>,
+){
+    let ship = query_ship.get_single().ok();
+    for (mut transform, mut velocity, homing, laser) in query.iter_mut() {
+        let position = transform.translation;
+        let target = if laser.player {
+            query_target.iter()
+                .map(|target| target.translation)
+                .filter(|target| target.z < position.z)
+                .min_by(|a, b| a.distance_squared(position).total_cmp(&b.distance_squared(position)))
+        } else {
+            ship.map(|ship| ship.translation).filter(|ship| ship.z > position.z)
+        };
+        let Some(target) = target else {
+            continue;
+        };
+        let speed = velocity.linvel.length();
+        let heading = velocity.linvel.normalize_or_zero();
+        let wanted = (target - position).normalize_or_zero();
+        let heading = heading.lerp(wanted, (homing.turn_rate * time.delta_seconds()).min(1.0)).normalize_or(heading);
+        velocity.linvel = heading * speed;
+        transform.look_to(heading, Vec3::Y);
+    }
+}
@@ -0,0 +1,84 @@
+use bevy::prelude::*;
+use bevy::input::gamepad::{GamepadConnection, GamepadConnectionEvent, GamepadInfo};
+use bevy_rapier3d::prelude::*;
+use planet_rust::actions::{Action, Binding, InputBindings, Rebinding};
+use planet_rust::components::{Laser, Ship};
+use planet_rust::sim::GameSim;
+
+fn ship_velocity(sim: &mut GameSim) -> Vec3 {
+    let world = sim.app.world_mut();
+    world.query_filtered::<&Velocity, With<Ship>>().single(world).linvel
+}
+
+#[test]
+fn a_half_tilted_stick_moves_at_half_speed() {
+    let mut sim = GameSim::default();
+    let gamepad = Gamepad::new(0);
+    sim.app.world_mut().send_event(GamepadConnectionEvent::new(gamepad, GamepadConnection::Connected(
+        GamepadInfo { name: "pad".to_string() })));
+    sim.run(1);
+
+    sim.app.world_mut().resource_mut::<Axis<GamepadAxis>>()
+        .set(GamepadAxis::new(gamepad, GamepadAxisType::LeftStickX), 0.5);
+    sim.run(10);
+    let half = ship_velocity(&mut sim).x;
+
+    sim.app.world_mut().resource_mut::<Axis<GamepadAxis>>()
+        .set(GamepadAxis::new(gamepad, GamepadAxisType::LeftStickX), 1.0);
+    sim.run(10);
+    let full = ship_velocity(&mut sim).x;
+
+    assert!(half > 0.0);
+    assert!((full / half - 2.0).abs() < 1e-3);
+}
+
+#[test]
+fn fire_can_be_rebound_at_runtime() {
+    let mut sim = GameSim::default();
+    sim.app.world_mut().resource_mut::<Rebinding>().start(Action::Fire);
+    sim.press(KeyCode::KeyF);
+    sim.run(1);
+    sim.release(KeyCode::KeyF);
+    sim.run(1);
+
+    let bindings = sim.app.world().resource::<InputBindings>();
+    assert!(bindings.bindings(Action::Fire).contains(&Binding::Key(KeyCode::KeyF)));
+    assert!(!bindings.bindings(Action::Fire).contains(&Binding::Key(KeyCode::Space)));
+
+    sim.press(KeyCode::Space);
+    sim.run(30);
+    assert_eq!(sim.count::<Laser>(), 0);
+
+    sim.release(KeyCode::Space);
+    sim.press(KeyCode::KeyF);
+    sim.run(30);
+    assert!(sim.count::<Laser>() >= 2);
+}
+
+#[test]
+fn an_axis_takes_two_keys() {
+    let mut sim = GameSim::default();
+    sim.app.world_mut().resource_mut::<Rebinding>().start(Action::MoveX);
+    for key in [KeyCode::KeyA, KeyCode::KeyD] {
+        sim.press(key);
+        sim.run(1);
+        sim.release(key);
+    }
+
+    let bindings = sim.app.world().resource::<InputBindings>();
+    assert!(bindings.bindings(Action::MoveX).contains(&Binding::Keys(KeyCode::KeyA, KeyCode::KeyD)));
+    assert!(bindings.bindings(Action::MoveX).contains(&Binding::GamepadAxis(GamepadAxisType::LeftStickX)));
+}
+
+#[test]
+fn missing_actions_keep_their_defaults() {
+    let path = std::env::temp_dir().join("planet-rust-bindings").join("bindings.ron");
+    let mut bindings = InputBindings::default();
+    bindings.rebind(Action::Fire, Binding::Key(KeyCode::KeyF));
+    bindings.actions.remove(&Action::Pause);
+    bindings.save(&path).unwrap();
+
+    let loaded = InputBindings::load(&path).unwrap();
+    assert_eq!(loaded.bindings(Action::Fire), bindings.bindings(Action::Fire));
+    assert_eq!(loaded.bindings(Action::Pause), InputBindings::default().bindings(Action::Pause));
+}
@@ -0,0 +1,153 @@
+use std::fs;
+use std::path::PathBuf;
+use bevy::ecs::system::RunSystemOnce;
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use planet_rust::asteroid::spawn_asteroid;
+use planet_rust::components::{Asteroid, Health};
+use planet_rust::events::{DamageEvent, DamageKind};
+use planet_rust::resources::{GameAssets, Score};
+use planet_rust::sim::{GameSim, GameSimPlugin};
+use planet_rust::weapon::Weapon;
+
+/// A game whose first level only spawns asteroids.
+fn sim_with_asteroids(name: &str, scale: (f32, f32)) -> GameSim {
+    let levels_path: PathBuf = std::env::temp_dir().join(format!("planet-rust-asteroid-{name}"));
+    fs::create_dir_all(&levels_path).unwrap();
+    fs::write(levels_path.join("level1.level.ron"), format!(
+        "(level: 1, name: \"{name}\", spawn_interval: 0.5, hits_to_advance: 40, \
+          spawns: [(kind: Asteroid(speed: (40.0, 40.0), scale: {scale:?}, hits: 8, points: 100))])"
+    )).unwrap();
+    GameSim::new(GameSimPlugin {
+        levels_path,
+        ..default()
+    })
+}
+
+fn asteroids(sim: &mut GameSim) -> Vec<(Entity, Asteroid, Vec3, f32)> {
+    let world = sim.app.world_mut();
+    world.query::<(Entity, &Asteroid, &Velocity, &Collider)>().iter(world)
+        .map(|(entity, asteroid, velocity, collider)| (entity, *asteroid, velocity.linvel,
+                                                       collider.as_ball().unwrap().radius()))
+        .collect()
+}
+
+fn spawn(sim: &mut GameSim, asteroid: Asteroid, translation: Vec3, linvel: Vec3) -> Entity {
+    sim.app.world_mut().run_system_once(move |mut commands: Commands, game_assets: Res<GameAssets>| {
+        spawn_asteroid(&mut commands, &game_assets, asteroid, translation, Velocity { linvel, ..default() });
+    });
+    sim.run(1);
+    let world = sim.app.world_mut();
+    world.query_filtered::<(Entity, &Transform), With<Asteroid>>().iter(world)
+        .find(|(_, transform)| transform.translation.distance(translation) < 1.0)
+        .map(|(entity, _)| entity)
+        .unwrap()
+}
+
+#[test]
+fn bigger_asteroids_are_heavier_tougher_and_worth_less() {
+    let small = Asteroid { scale: 4.0, toughness: 0.25, points: 100, min_scale: 4.0, chip_damage: 0.0 };
+    let big = Asteroid { scale: 16.0, ..small };
+
+    assert!(big.radius() > small.radius());
+    assert!(big.mass() > small.mass() * 60.0);
+    assert_eq!((small.health(), big.health()), (1.0, 4.0));
+    assert_eq!((small.score(), big.score()), (100, 25));
+    assert_eq!(big.piece().map(|piece| piece.scale), Some(8.0));
+    assert_eq!(small.piece(), None);
+}
+
+#[test]
+fn the_collider_and_health_follow_the_size() {
+    let mut sim = sim_with_asteroids("size", (4.0, 28.0));
+    sim.run(150);
+    let spawned = asteroids(&mut sim);
+    assert!(spawned.len() > 2);
+    for (entity, asteroid, _, radius) in spawned {
+        assert_eq!(radius, asteroid.radius());
+        assert_eq!(sim.app.world().get::<Health>(entity).unwrap().max, asteroid.health());
+    }
+}
+
+#[test]
+fn a_big_asteroid_splits_into_pieces_flying_apart() {
+    let mut sim = sim_with_asteroids("split", (4.0, 4.0));
+    let big = Asteroid { scale: 16.0, toughness: 0.25, points: 100, min_scale: 4.0, chip_damage: 0.0 };
+    let entity = spawn(&mut sim, big, Vec3::new(0.0, 0.0, -200.0), Vec3::Z * 40.0);
+
+    sim.app.world_mut().send_event(DamageEvent {
+        target: entity,
+        amount: 100.0,
+        kind: DamageKind::Projectile,
+    });
+    sim.run(2);
+
+    let pieces = asteroids(&mut sim);
+    assert_eq!(pieces.len(), 3);
+    assert!(pieces.iter().all(|(_, piece, _, _)| piece.scale == 8.0));
+    let sideways: Vec<Vec2> = pieces.iter().map(|(_, _, velocity, _)| velocity.truncate()).collect();
+    assert!(sideways.iter().all(|v| v.length() > 10.0));
+    assert!(sideways[0].dot(sideways[1]) < 0.0 || sideways[0].dot(sideways[2]) < 0.0);
+}
+
+#[test]
+fn asteroids_tumble() {
+    let mut sim = sim_with_asteroids("spin", (4.0, 28.0));
+    sim.run(150);
+    let world = sim.app.world_mut();
+    let spins: Vec<f32> = world.query_filtered::<&Velocity, With<Asteroid>>().iter(world)
+        .map(|velocity| velocity.angvel.length())
+        .collect();
+    assert!(!spins.is_empty());
+    assert!(spins.iter().all(|spin| *spin > 0.0));
+}
+
+#[test]
+fn crashing_asteroids_bounce_and_chip_each_other() {
+    let mut sim = sim_with_asteroids("chip", (4.0, 4.0));
+    let rock = Asteroid { scale: 8.0, toughness: 0.5, points: 100, min_scale: 8.0, chip_damage: 1.0 };
+    let left = spawn(&mut sim, rock, Vec3::new(-20.0, 40.0, -300.0), Vec3::X * 30.0);
+    let right = spawn(&mut sim, rock, Vec3::new(20.0, 40.0, -300.0), Vec3::X * -30.0);
+    sim.run(60);
+
+    let world = sim.app.world();
+    assert!(world.get::<Velocity>(left).unwrap().linvel.x < 0.0);
+    assert!(world.get::<Velocity>(right).unwrap().linvel.x > 0.0);
+    assert_eq!(world.get::<Health>(left).unwrap().current, 3.0);
+    assert_eq!(world.get::<Health>(right).unwrap().current, 3.0);
+    assert_eq!(world.resource::<Score>().points, 0);
+}
+
+#[test]
+fn a_chip_that_destroys_an_asteroid_scores_nothing() {
+    let mut sim = sim_with_asteroids("chip-kill", (4.0, 4.0));
+    let rock = Asteroid { scale: 4.0, toughness: 0.25, points: 100, min_scale: 4.0, chip_damage: 1.0 };
+    let left = spawn(&mut sim, rock, Vec3::new(-10.0, 40.0, -300.0), Vec3::X * 30.0);
+    let right = spawn(&mut sim, rock, Vec3::new(10.0, 40.0, -300.0), Vec3::X * -30.0);
+    let hits = sim.ship().hits;
+    sim.run(60);
+
+    assert!(sim.app.world().get_entity(left).is_none());
+    assert!(sim.app.world().get_entity(right).is_none());
+    assert_eq!(sim.app.world().resource::<Score>().points, 0);
+    assert_eq!(sim.ship().hits, hits);
+}
+
+#[test]
+fn shots_push_asteroids() {
+    let mut sim = sim_with_asteroids("push", (4.0, 4.0));
+    let rock = Asteroid { scale: 8.0, toughness: 10.0, points: 100, min_scale: 8.0, chip_damage: 0.0 };
+    let ahead = sim.ship_transform().translation + Vec3::new(0.0, 0.0, -60.0);
+    let entity = spawn(&mut sim, rock, ahead, Vec3::ZERO);
+    // one shot from both guns
+    sim.press(KeyCode::Space);
+    sim.run(2);
+    sim.release(KeyCode::Space);
+    sim.run(20);
+
+    // the asteroid moves with exactly the momentum of the two lasers
+    let spec = Weapon::Laser.spec();
+    let expected = Vec3::NEG_Z * 2.0 * spec.speed * spec.mass / rock.mass();
+    let velocity = sim.app.world().get::<Velocity>(entity).unwrap().linvel;
+    assert!((velocity - expected).length() < expected.length() * 0.01, "{velocity} != {expected}");
+}
@@ -0,0 +1,110 @@
+use std::fs;
+use std::path::Path;
+use bevy::asset::AssetPath;
+use bevy::audio::Decodable;
+use bevy::prelude::*;
+use bevy_asset_loader::mapped::{AssetFileName, MapKey};
+use planet_rust::audio::{AudioSettings, MusicTrack, NullAudio, Sound, Synth};
+use planet_rust::components::Ship;
+use planet_rust::events::{CreateEffectEvent, DamageEvent, DamageKind};
+use planet_rust::resources::{GameAssets, Level};
+use planet_rust::sim::GameSim;
+
+fn read_synth(path: &Path) -> Synth {
+    ron::from_str(&fs::read_to_string(path).unwrap())
+        .unwrap_or_else(|e| panic!("invalid sound {}: {e}", path.display()))
+}
+
+fn played(sim: &GameSim, sound: Sound) -> Vec<Vec3> {
+    sim.app.world().resource::<NullAudio>().played.iter()
+        .filter(|(played, _)| *played == sound)
+        .map(|(_, translation)| *translation)
+        .collect()
+}
+
+fn tracks(sim: &mut GameSim) -> Vec<(usize, f32, bool)> {
+    let world = sim.app.world_mut();
+    let mut tracks: Vec<_> = world.query::<&MusicTrack>().iter(world)
+        .map(|track| (track.level, track.fade, track.fading_in))
+        .collect();
+    tracks.sort_by_key(|(level, _, _)| *level);
+    tracks
+}
+
+#[test]
+fn every_sound_file_decodes() {
+    let sounds = Path::new("assets/sounds");
+    let mut files = vec![sounds.join("laser.sfx.ron"), sounds.join("explosion.sfx.ron"), sounds.join("hit.sfx.ron")];
+    files.extend(fs::read_dir(sounds.join("music")).unwrap().map(|entry| entry.unwrap().path()));
+    assert!(files.len() >= 6);
+    for file in files {
+        let synth = read_synth(&file);
+        let samples: Vec<f32> = synth.decoder().collect();
+        assert_eq!(samples.len(), synth.notes.iter().map(|note| (note.length * 44_100.0) as usize).sum::<usize>());
+        assert!(samples.iter().all(|sample| sample.abs() <= 1.0), "{} clips", file.display());
+        assert!(samples.iter().any(|sample| sample.abs() > 0.01), "{} is silent", file.display());
+    }
+}
+
+#[test]
+fn shots_explosions_and_hits_are_heard_where_they_happen() {
+    let mut sim = GameSim::default();
+    sim.press(KeyCode::Space);
+    sim.run(2);
+    let ship = sim.ship_transform().translation;
+    assert!(played(&sim, Sound::Laser).contains(&ship));
+
+    let explosion = Vec3::new(5.0, 0.0, -200.0);
+    sim.app.world_mut().send_event(CreateEffectEvent(explosion, 1.0));
+    let world = sim.app.world_mut();
+    let target = world.query_filtered::<Entity, With<Ship>>().single(world);
+    world.send_event(DamageEvent {
+        target,
+        amount: 1.0,
+        kind: DamageKind::Projectile,
+    });
+    sim.run(1);
+    assert!(played(&sim, Sound::Explosion).contains(&explosion));
+    assert!(!played(&sim, Sound::Hit).is_empty());
+}
+
+#[test]
+fn the_music_crossfades_when_the_level_changes() {
+    let mut sim = GameSim::default();
+    let world = sim.app.world_mut();
+    for name in ["drift.sfx.ron", "rocks.sfx.ron", "canyon.sfx.ron"] {
+        let synth = read_synth(&Path::new("assets/sounds/music").join(name));
+        let handle = world.resource_mut::<Assets<Synth>>().add(synth);
+        world.resource_mut::<GameAssets>().music
+            .insert(AssetFileName::from_asset_path(&AssetPath::from(format!("sounds/music/{name}"))), handle);
+    }
+
+    sim.app.world_mut().resource_mut::<Level>().value = 2;
+    sim.run(150);
+    assert_eq!(tracks(&mut sim), vec![(2, 1.0, true)]);
+
+    sim.app.world_mut().resource_mut::<Level>().value = 3;
+    sim.run(60);
+    let fading = tracks(&mut sim);
+    assert_eq!(fading.len(), 2);
+    assert!(!fading[0].2 && fading[0].1 < 1.0 && fading[0].1 > 0.0);
+    assert!(fading[1].2 && fading[1].1 > 0.0 && fading[1].1 < 1.0);
+
+    sim.run(90);
+    assert_eq!(tracks(&mut sim), vec![(3, 1.0, true)]);
+}
+
+#[test]
+fn audio_settings_survive_a_restart() {
+    let path = std::env::temp_dir().join("planet-rust-audio-test").join("audio.ron");
+    let settings = AudioSettings {
+        master: 0.5,
+        music: 0.2,
+        sfx: 1.0,
+    };
+    settings.save(&path).unwrap();
+    let loaded = AudioSettings::load(&path).unwrap();
+    assert_eq!(loaded, settings);
+    assert_eq!(loaded.music_volume(), 0.1);
+    assert_eq!(loaded.sfx_volume(), 0.5);
+}
@@ -0,0 +1,121 @@
+use std::fs;
+use std::path::PathBuf;
+use bevy::prelude::*;
+use planet_rust::components::{Laser, Opponent, Terrain, Tower};
+use planet_rust::game_state::GameState;
+use planet_rust::sim::{GameSim, GameSimPlugin};
+
+/// A game whose first level only spawns the given fighter.
+fn sim_with_fighter(name: &str, fighter: &str) -> GameSim {
+    let levels_path: PathBuf = std::env::temp_dir().join(format!("planet-rust-behaviour-{name}"));
+    fs::create_dir_all(&levels_path).unwrap();
+    fs::write(levels_path.join("level1.level.ron"), format!(
+        "(level: 1, name: \"{name}\", spawn_interval: 0.5, hits_to_advance: 40, \
+          spawns: [(kind: Fighter(speed: (60.0, 60.0), cooldown: (0.2, 0.2), hits: 1, {fighter}))])"
+    )).unwrap();
+    GameSim::new(GameSimPlugin {
+        levels_path,
+        ..default()
+    })
+}
+
+fn opponent_translations(sim: &mut GameSim) -> Vec<Vec3> {
+    let world = sim.app.world_mut();
+    world.query_filtered::<&Transform, With<Opponent>>().iter(world).map(|t| t.translation).collect()
+}
+
+#[test]
+fn a_formation_spawns_together() {
+    let mut sim = sim_with_fighter("formation", "flight: Formation(count: 5, spacing: 8.0)");
+    sim.run(31);
+
+    let fighters = opponent_translations(&mut sim);
+    assert_eq!(fighters.len(), 5);
+    let leader = fighters.iter().max_by(|a, b| a.z.total_cmp(&b.z)).unwrap();
+    assert!(fighters.iter().all(|f| f.z < leader.z + 0.1 && (f.y - leader.y).abs() < 0.1));
+}
+
+#[test]
+fn weaving_fighters_move_sideways() {
+    let mut sim = sim_with_fighter("weave", "flight: Weave(amplitude: 8.0, frequency: 0.5)");
+    sim.run(31);
+    let start = opponent_translations(&mut sim)[0];
+    sim.run(30);
+    let later = opponent_translations(&mut sim)[0];
+
+    assert!((later.x - start.x).abs() > 4.0);
+}
+
+#[test]
+fn kamikazes_home_in_on_the_ship() {
+    let mut sim = sim_with_fighter("kamikaze", "flight: Kamikaze(turn_rate: 3.0)");
+    sim.run(31);
+    let ship = sim.ship_transform().translation;
+    let start = opponent_translations(&mut sim)[0];
+    sim.run(90);
+    let later = opponent_translations(&mut sim).into_iter()
+        .find(|o| o.z > start.z).unwrap();
+
+    let off_course = |p: Vec3| Vec2::new(p.x - ship.x, p.y - ship.y).length();
+    assert!(off_course(later) < off_course(start));
+}
+
+#[test]
+fn leading_shots_aim_ahead_of_a_moving_ship() {
+    let mut sim = sim_with_fighter("lead", "lead_shots: true");
+    // the fighter opens fire about 130 frames in, every 12 frames
+    sim.run(133);
+    sim.press(KeyCode::ArrowRight);
+    sim.run(13);
+
+    let ship = sim.ship_transform().translation;
+    let world = sim.app.world_mut();
+    let (newest, _) = world.query::<(&Transform, &Laser)>().iter(world)
+        .filter(|(_, laser)| !laser.player)
+        .min_by(|(a, _), (b, _)| a.translation.z.total_cmp(&b.translation.z))
+        .unwrap();
+    let direction = newest.forward();
+    let hit = newest.translation + *direction * (ship.z - newest.translation.z) / direction.z;
+    // the ship flies right, so the shot goes to where it will be
+    assert!(hit.x > ship.x + 2.0);
+}
+
+/// A game that starts in a tower run valley, sped up so it reaches the ship quickly.
+fn sim_with_valley(name: &str, towers: &str) -> GameSim {
+    let levels_path: PathBuf = std::env::temp_dir().join(format!("planet-rust-valley-{name}"));
+    fs::create_dir_all(&levels_path).unwrap();
+    fs::write(levels_path.join("level1.level.ron"), format!(
+        "(level: 1, name: \"{name}\", spawn_interval: 0.4, hits_to_advance: 2, \
+          spawns: [(kind: Platform(speed: 120.0, towers: {towers}))])"
+    )).unwrap();
+    GameSim::new(GameSimPlugin {
+        levels_path,
+        ..default()
+    })
+}
+
+#[test]
+fn scraping_the_valley_costs_shields() {
+    let mut sim = sim_with_valley("scrape", "None");
+    sim.run(150);
+    assert!(sim.count::<Terrain>() > 0);
+    assert_eq!(sim.ship_health().fraction(), 1.0);
+
+    sim.press(KeyCode::ArrowLeft);
+    sim.press(KeyCode::ArrowDown);
+    sim.run(60);
+    assert!(sim.ship_health().fraction() < 1.0);
+}
+
+#[test]
+fn destroyed_towers_count_towards_the_level() {
+    let mut sim = sim_with_valley("towers",
+                                  "Some((chance: 1.0, hits: 1, cooldown: (5.0, 5.0), points: 100))");
+    sim.press(KeyCode::ArrowDown);
+    sim.run(30);
+    assert!(sim.count::<Tower>() > 0);
+
+    sim.press(KeyCode::Space);
+    sim.run(240);
+    assert!(sim.ship().hits < 2 || sim.state() == GameState::Won);
+}
@@ -0,0 +1,38 @@
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use planet_rust::canyon::{Canyon, Profile};
+
+fn segments(seed: u64, count: usize) -> Vec<(Profile, Profile)> {
+    let mut rng = ChaCha8Rng::seed_from_u64(seed);
+    let mut canyon = Canyon::default();
+    (0..count).map(|_| canyon.next_segment(&mut rng)).collect()
+}
+
+#[test]
+fn segments_fit_together() {
+    let segments = segments(7, 200);
+    for pair in segments.windows(2) {
+        assert_eq!(pair[0].1, pair[1].0);
+    }
+    for (near, far) in &segments {
+        assert!((near.half_width - far.half_width).abs() <= 0.6 + 1e-5);
+        assert!((near.floor - far.floor).abs() <= 0.5 + 1e-5);
+    }
+}
+
+#[test]
+fn the_canyon_changes_shape() {
+    let segments = segments(7, 200);
+    let widths: Vec<f32> = segments.iter().map(|(near, _)| near.half_width).collect();
+    let floors: Vec<f32> = segments.iter().map(|(near, _)| near.floor).collect();
+    let range = |values: &[f32]| values.iter().cloned().fold(f32::MIN, f32::max)
+        - values.iter().cloned().fold(f32::MAX, f32::min);
+    assert!(range(&widths) > 3.0);
+    assert!(range(&floors) > 3.0);
+}
+
+#[test]
+fn the_same_seed_builds_the_same_canyon() {
+    assert_eq!(segments(3, 50), segments(3, 50));
+    assert_ne!(segments(3, 50), segments(4, 50));
+}
@@ -0,0 +1,94 @@
+use std::path::PathBuf;
+use bevy::prelude::*;
+use clap::Parser;
+use planet_rust::cli::Cli;
+use planet_rust::components::Planet;
+use planet_rust::game_state::GameState;
+use bevy::window::{PrimaryWindow, WindowMode};
+use planet_rust::settings::{Resolution, Settings, SettingsPlugin};
+use planet_rust::sim::{GameSim, GameSimPlugin};
+use planet_rust::skybox::SkyboxPlugin;
+
+fn parse(args: &[&str]) -> Result<Cli, clap::Error> {
+    Cli::try_parse_from(["planet-rust"].iter().chain(args))
+}
+
+#[test]
+fn without_arguments_it_is_the_usual_game() {
+    let cli = parse(&[]).unwrap();
+    assert_eq!(cli.level, 1);
+    assert_eq!(cli.seed, None);
+    assert!(!cli.headless);
+    let mut window = Settings { fullscreen: true, ..Default::default() }.window();
+    cli.apply(&mut window);
+    assert_eq!(window.mode, WindowMode::BorderlessFullscreen);
+}
+
+#[test]
+fn a_test_run_is_set_up_from_the_command_line() {
+    let cli = parse(&["--level", "3", "--seed", "42", "--headless", "--frames", "600"]).unwrap();
+    assert_eq!((cli.level, cli.seed, cli.headless, cli.frames), (3, Some(42), true, Some(600)));
+
+    let cli = parse(&["--replay", "run.replay", "--headless"]).unwrap();
+    assert_eq!(cli.replay, Some(PathBuf::from("run.replay")));
+
+    let cli = parse(&["--fullscreen", "--resolution", "1280x720", "--record", "run.replay"]).unwrap();
+    assert_eq!(cli.resolution, Some(Resolution { width: 1280, height: 720 }));
+    assert_eq!(cli.record, Some(PathBuf::from("run.replay")));
+}
+
+#[test]
+fn window_flags_are_not_kept_as_settings() {
+    let cli = parse(&["--fullscreen", "--resolution", "1280x720"]).unwrap();
+    let mut window = Settings::default().window();
+    cli.apply(&mut window);
+    let spawn_window = move |app: &mut App| {
+        app.world_mut().spawn((window.clone(), PrimaryWindow));
+    };
+    let mut sim = GameSim::with_plugins(GameSimPlugin::default(), (SettingsPlugin, spawn_window));
+    sim.run(1);
+
+    let world = sim.app.world_mut();
+    let window = world.query_filtered::<&Window, With<PrimaryWindow>>().single(world);
+    assert_eq!(window.mode, WindowMode::BorderlessFullscreen);
+    assert_eq!((window.resolution.width(), window.resolution.height()), (1280.0, 720.0));
+    assert_eq!(*sim.app.world().resource::<Settings>(), Settings::default());
+}
+
+#[test]
+fn contradicting_arguments_are_refused() {
+    for args in [&["--level", "0"][..], &["--resolution", "1280"], &["--headless"], &["--frames", "10"],
+                 &["--fullscreen", "--windowed"], &["--record", "a", "--replay", "b"],
+                 &["--replay", "a", "--seed", "1"], &["--replay", "a", "--level", "2"]] {
+        assert!(parse(args).is_err(), "{args:?}");
+    }
+}
+
+#[test]
+fn every_game_starts_at_the_start_level() {
+    let mut sim = GameSim::new(GameSimPlugin { level: 3, ..Default::default() });
+    assert_eq!(sim.level(), 3);
+    assert_eq!(sim.ship().hits, 25);
+
+    sim.set_state(GameState::Restart);
+    sim.run(1);
+    assert_eq!(sim.state(), GameState::Running);
+    assert_eq!(sim.level(), 3);
+}
+
+#[test]
+fn a_later_start_level_looks_like_it_was_flown_to() {
+    let mut sim = GameSim::with_plugins(GameSimPlugin { level: 3, ..Default::default() }, SkyboxPlugin);
+    assert_eq!(sim.count::<Planet>(), 0);
+
+    sim.run(60);
+    let world = sim.app.world_mut();
+    let skybox = world.query::<(&Name, &Transform)>().iter(world)
+        .find(|(name, _)| name.as_str() == "Skybox")
+        .map(|(_, transform)| transform.rotation)
+        .unwrap();
+    assert_ne!(skybox, Quat::IDENTITY);
+
+    let mut sim = GameSim::default();
+    assert_eq!(sim.count::<Planet>(), 2);
+}
@@ -0,0 +1,119 @@
+#![cfg(feature = "debug")]
+
+use std::fs;
+use bevy::prelude::*;
+use planet_rust::components::{Asteroid, Boss, Opponent, Planet, Ship, WeakPoint};
+use planet_rust::console::{Console, ConsoleCommand, ConsolePlugin};
+use planet_rust::events::{DamageEvent, DamageKind};
+use planet_rust::resources::GameStats;
+use planet_rust::rng::GameRng;
+use planet_rust::sim::{GameSim, GameSimPlugin};
+
+fn console_sim() -> GameSim {
+    GameSim::with_plugins(GameSimPlugin::default(), ConsolePlugin::default())
+}
+
+/// Types the line and runs the frame that executes it, returns the console's answer.
+fn type_line(sim: &mut GameSim, line: &str) -> String {
+    sim.app.world_mut().resource_mut::<Console>().submit(line);
+    sim.run(1);
+    sim.app.world().resource::<Console>().log.last().cloned().unwrap()
+}
+
+#[test]
+fn commands_are_parsed_with_their_arguments() {
+    assert_eq!("spawn asteroid 20".parse(), Ok(ConsoleCommand::Spawn("asteroid".to_string(), 20)));
+    assert_eq!("spawn fighter".parse(), Ok(ConsoleCommand::Spawn("fighter".to_string(), 1)));
+    assert_eq!("  timescale 0.25 ".parse(), Ok(ConsoleCommand::TimeScale(0.25)));
+    assert_eq!("kill_all".parse(), Ok(ConsoleCommand::KillAll));
+    assert!("level".parse::<ConsoleCommand>().is_err());
+    assert!("shields full".parse::<ConsoleCommand>().is_err());
+    assert!("fly home".parse::<ConsoleCommand>().is_err());
+}
+
+#[test]
+fn level_jumps_straight_to_the_level() {
+    let mut sim = console_sim();
+    // in the middle of the first boss fight
+    sim.ship().hits = 0;
+    sim.run(2);
+    assert_eq!(sim.count::<Boss>(), 1);
+
+    assert_eq!(type_line(&mut sim, "level 3"), "level 3");
+    sim.run(1);
+    assert_eq!(sim.level(), 3);
+    assert_eq!(sim.ship().hits, 25);
+    assert_eq!(sim.count::<Boss>(), 0);
+    assert_eq!(sim.count::<WeakPoint>(), 0);
+    assert_eq!(sim.count::<Planet>(), 0);
+
+    assert_eq!(type_line(&mut sim, "level 9"), "there is no level 9");
+    assert_eq!(sim.level(), 3);
+}
+
+#[test]
+fn shields_and_god_mode_protect_the_ship() {
+    let mut sim = console_sim();
+    type_line(&mut sim, "shields 0.25");
+    assert_eq!(sim.ship_health().fraction(), 0.25);
+
+    assert_eq!(type_line(&mut sim, "god"), "god mode on");
+    let world = sim.app.world_mut();
+    let ship = world.query_filtered::<Entity, With<Ship>>().single(world);
+    for _ in 0..10 {
+        sim.app.world_mut().send_event(DamageEvent {
+            target: ship,
+            amount: 1.0,
+            kind: DamageKind::Projectile,
+        });
+        sim.run(1);
+    }
+    assert_eq!(sim.ship_health().fraction(), 0.25);
+
+    assert_eq!(type_line(&mut sim, "god"), "god mode off");
+    sim.app.world_mut().send_event(DamageEvent {
+        target: ship,
+        amount: 1.0,
+        kind: DamageKind::Projectile,
+    });
+    sim.run(1);
+    assert!(sim.ship_health().fraction() < 0.25);
+}
+
+#[test]
+fn spawn_and_kill_all_go_through_the_game() {
+    let mut sim = console_sim();
+    let before = sim.count::<Asteroid>();
+    assert_eq!(type_line(&mut sim, "spawn asteroid 20"), "spawned 20 asteroid");
+    assert!(sim.count::<Asteroid>() >= before + 20);
+
+    let opponents = sim.count::<Opponent>();
+    type_line(&mut sim, "kill_all");
+    sim.run(1);
+    assert_eq!(sim.app.world().resource::<GameStats>().kills as usize, opponents);
+    assert!(sim.count::<Asteroid>() < 20);
+}
+
+#[test]
+fn timescale_and_seed_change_the_clock_and_the_dice() {
+    let mut sim = console_sim();
+    type_line(&mut sim, "timescale 0.25");
+    assert_eq!(sim.app.world().resource::<Time<Virtual>>().relative_speed(), 0.25);
+    assert_eq!(type_line(&mut sim, "timescale 0"), "the time scale has to be above 0");
+
+    type_line(&mut sim, "seed 42");
+    assert_eq!(sim.app.world().resource::<GameRng>().seed, 42);
+}
+
+#[test]
+fn the_startup_script_runs_when_a_game_starts() {
+    let dir = std::env::temp_dir().join("planet-rust-console-test");
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("setup.txt");
+    fs::write(&path, "# straight to the valley\nlevel 3\n\nshields 0.5\n").unwrap();
+
+    let mut sim = GameSim::with_plugins(GameSimPlugin::default(), ConsolePlugin { script: Some(path) });
+    sim.run(1);
+    assert_eq!(sim.level(), 3);
+    assert_eq!(sim.ship_health().fraction(), 0.5);
+}
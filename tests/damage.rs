@@ -0,0 +1,106 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use planet_rust::components::{Armour, Damage, Health, Shield, Ship};
+use planet_rust::damage::SHIP_RAM_DAMAGE;
+use planet_rust::events::{DamageEvent, DamageKind};
+use planet_rust::game_state::GameState;
+use planet_rust::resources::Score;
+use planet_rust::sim::GameSim;
+
+fn hit(sim: &mut GameSim, target: Entity, amount: f32) {
+    sim.app.world_mut().send_event(DamageEvent {
+        target,
+        amount,
+        kind: DamageKind::Projectile,
+    });
+    sim.run(1);
+}
+
+fn ship_entity(sim: &mut GameSim) -> Entity {
+    let world = sim.app.world_mut();
+    world.query_filtered::<Entity, With<Ship>>().single(world)
+}
+
+fn health(sim: &mut GameSim, entity: Entity) -> f32 {
+    sim.app.world().get::<Health>(entity).map_or(0.0, |health| health.current)
+}
+
+#[test]
+fn the_ship_cannot_be_hurt_right_after_a_hit() {
+    let mut sim = GameSim::default();
+    let ship = ship_entity(&mut sim);
+    sim.app.world_mut().resource_mut::<Score>().streak = 7;
+
+    hit(&mut sim, ship, 1.0);
+    hit(&mut sim, ship, 1.0);
+    assert_eq!(health(&mut sim, ship), 19.0);
+    assert_eq!(sim.app.world().resource::<Score>().streak, 0);
+
+    sim.run(30);
+    hit(&mut sim, ship, 1.0);
+    assert_eq!(health(&mut sim, ship), 18.0);
+}
+
+#[test]
+fn the_game_ends_when_hits_use_up_the_shields_exactly() {
+    let mut sim = GameSim::default();
+    let ship = ship_entity(&mut sim);
+    for _ in 0..5 {
+        hit(&mut sim, ship, 4.0);
+        sim.run(30);
+    }
+    assert_eq!(health(&mut sim, ship), 0.0);
+    assert_eq!(sim.state(), GameState::End);
+}
+
+#[test]
+fn shields_soak_up_hits_before_the_armour() {
+    let mut sim = GameSim::default();
+    let opponent = sim.spawn_opponent(-Vec3::Z * 200.0, 4.0);
+    sim.app.world_mut().entity_mut(opponent).insert((
+        Shield { current: 1.0, max: 1.0, recharge: 0.0 },
+        Armour { reduction: 0.5 },
+    ));
+
+    hit(&mut sim, opponent, 3.0);
+    assert_eq!(health(&mut sim, opponent), 3.0);
+    hit(&mut sim, opponent, 2.0);
+    assert_eq!(health(&mut sim, opponent), 2.0);
+}
+
+#[test]
+fn an_opponent_out_of_health_is_destroyed_and_scored() {
+    let mut sim = GameSim::default();
+    let opponent = sim.spawn_opponent(-Vec3::Z * 200.0, 2.0);
+
+    hit(&mut sim, opponent, 1.0);
+    assert!(sim.app.world().get_entity(opponent).is_some());
+    hit(&mut sim, opponent, 1.0);
+    sim.run(1);
+    assert!(sim.app.world().get_entity(opponent).is_none());
+    assert_eq!(sim.app.world().resource::<Score>().points, 100);
+    assert_eq!(sim.ship().hits, 39);
+}
+
+fn spawn_rammer(sim: &mut GameSim, health: f32) -> Entity {
+    let opponent = sim.spawn_opponent(-Vec3::Z * 20.0, health);
+    sim.app.world_mut().entity_mut(opponent).insert((Velocity::linear(Vec3::Z * 60.0), Damage { amount: 2.0 }));
+    opponent
+}
+
+#[test]
+fn ramming_damages_both_and_scores_a_kill() {
+    let mut sim = GameSim::default();
+    let ship = ship_entity(&mut sim);
+    let opponent = spawn_rammer(&mut sim, 1.0);
+    sim.run(30);
+    assert!(sim.app.world().get_entity(opponent).is_none());
+    assert_eq!(health(&mut sim, ship), 18.0);
+    assert_eq!(sim.app.world().resource::<Score>().points, 100);
+    assert_eq!(sim.ship().hits, 39);
+
+    // too much for the ship to ram through
+    let opponent = spawn_rammer(&mut sim, 10.0);
+    sim.run(30);
+    assert_eq!(health(&mut sim, opponent), 10.0 - SHIP_RAM_DAMAGE);
+}
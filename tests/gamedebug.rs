@@ -0,0 +1,40 @@
+#![cfg(feature = "debug")]
+
+use bevy::prelude::*;
+use planet_rust::components::MainCamera;
+use planet_rust::gamedebug::switch_camera;
+use planet_rust::orbitcamera::OrbitCamera;
+use planet_rust::sim::GameSim;
+
+fn active(sim: &mut GameSim) -> (bool, bool, bool) {
+    let world = sim.app.world_mut();
+    let main = world.query_filtered::<&Camera, With<MainCamera>>().single(world).is_active;
+    let (orbit_camera, orbit) = world.query::<(&Camera, &OrbitCamera)>().single(world);
+    (main, orbit_camera.is_active, orbit.enabled)
+}
+
+#[test]
+fn the_debug_camera_switches_back_and_forth() {
+    let mut sim = GameSim::default();
+    sim.app.add_systems(Update, switch_camera);
+    let world = sim.app.world_mut();
+    world.spawn((Camera::default(), MainCamera));
+    world.spawn((Camera { is_active: false, ..default() }, OrbitCamera { enabled: false, ..default() }));
+
+    sim.press(KeyCode::KeyO);
+    sim.run(1);
+    assert_eq!(active(&mut sim), (false, true, true));
+    let ship = sim.ship_transform().translation;
+    let world = sim.app.world_mut();
+    assert_eq!(world.query::<&OrbitCamera>().single(world).center, ship);
+
+    // holding the key does not switch again
+    sim.run(5);
+    assert_eq!(active(&mut sim), (false, true, true));
+
+    sim.release(KeyCode::KeyO);
+    sim.run(1);
+    sim.press(KeyCode::KeyO);
+    sim.run(1);
+    assert_eq!(active(&mut sim), (true, false, false));
+}
@@ -0,0 +1,28 @@
+use planet_rust::highscore::HighScores;
+
+#[test]
+fn the_table_keeps_the_best_ten_in_order() {
+    let mut high_scores = HighScores::default();
+    for score in 1..=12 {
+        high_scores.insert("pilot", score * 100, 1);
+    }
+    assert_eq!(high_scores.entries.len(), 10);
+    assert_eq!(high_scores.entries[0].score, 1200);
+    assert_eq!(high_scores.entries[9].score, 300);
+
+    assert!(!high_scores.qualifies(300));
+    assert!(high_scores.qualifies(301));
+    high_scores.insert("   ", 650, 2);
+    assert_eq!(high_scores.entries[6].name, "???");
+    assert_eq!(high_scores.entries[6].level, 2);
+}
+
+#[test]
+fn the_table_survives_a_round_trip() {
+    let path = std::env::temp_dir().join("planet-rust-highscores").join("highscores.ron");
+    let mut high_scores = HighScores::default();
+    high_scores.insert("ferris", 4200, 3);
+    high_scores.save(&path).unwrap();
+
+    assert_eq!(HighScores::load(&path).unwrap(), high_scores);
+}
@@ -0,0 +1,75 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use planet_rust::components::Ship;
+use planet_rust::events::{CreateEffectEvent, DamageEvent, DamageKind};
+use planet_rust::particles::Particle;
+use planet_rust::sim::GameSim;
+
+fn particles(sim: &mut GameSim) -> Vec<(Vec3, f32, usize)> {
+    let world = sim.app.world_mut();
+    world.query::<(&Transform, &Particle)>().iter(world)
+        .map(|(transform, particle)| (transform.translation, transform.scale.x, particle.step))
+        .collect()
+}
+
+/// How many particles an explosion of that size throws.
+fn explode(size: f32) -> usize {
+    let mut sim = GameSim::default();
+    let before = particles(&mut sim).len();
+    sim.app.world_mut().send_event(CreateEffectEvent(Vec3::new(0.0, 20.0, -200.0), size));
+    sim.run(2);
+    particles(&mut sim).len() - before
+}
+
+#[test]
+fn bigger_things_make_bigger_explosions() {
+    let fighter = explode(1.0);
+    let rock = explode(4.0);
+    assert!(fighter >= 30);
+    assert!(rock > 3 * fighter, "{rock} vs {fighter}");
+}
+
+#[test]
+fn particles_fade_and_die_without_physics() {
+    let mut sim = GameSim::default();
+    let exhaust = particles(&mut sim).len();
+    sim.app.world_mut().send_event(CreateEffectEvent(Vec3::new(0.0, 20.0, -200.0), 1.0));
+    sim.run(60);
+
+    let world = sim.app.world_mut();
+    assert_eq!(world.query_filtered::<(), (With<Particle>, With<RigidBody>)>().iter(world).count(), 0);
+    let debris: Vec<_> = particles(&mut sim).into_iter()
+        .filter(|(translation, _, _)| translation.y > 10.0)
+        .collect();
+    assert!(!debris.is_empty());
+    assert!(debris.iter().all(|(_, size, step)| *size < 0.4 && *step > 0));
+
+    sim.run(60);
+    assert!(particles(&mut sim).len() <= exhaust + 30);
+}
+
+#[test]
+fn the_ship_leaves_an_exhaust_trail() {
+    let mut sim = GameSim::default();
+    sim.run(30);
+    let ship = sim.ship_transform().translation;
+    let trail = particles(&mut sim);
+    assert!(trail.len() > 5);
+    assert!(trail.iter().all(|(translation, _, _)| translation.z > ship.z));
+}
+
+#[test]
+fn shield_hits_throw_sparks() {
+    let mut sim = GameSim::default();
+    sim.run(30);
+    let before = particles(&mut sim).len();
+    let world = sim.app.world_mut();
+    let ship = world.query_filtered::<Entity, With<Ship>>().single(world);
+    world.send_event(DamageEvent {
+        target: ship,
+        amount: 1.0,
+        kind: DamageKind::Projectile,
+    });
+    sim.run(2);
+    assert!(particles(&mut sim).len() >= before + 12);
+}
@@ -0,0 +1,81 @@
+use bevy::prelude::*;
+use planet_rust::events::CreateEffectEvent;
+use planet_rust::pool::{EntityPool, PoolKind, Pooled, SharedHandles};
+use planet_rust::resources::GameStats;
+use planet_rust::components::Terrain;
+use planet_rust::sim::{GameSim, GameSimPlugin};
+
+/// Meshes and materials, leaving out those made once per colour.
+fn asset_counts(sim: &GameSim) -> (usize, usize) {
+    let world = sim.app.world();
+    (world.resource::<Assets<Mesh>>().len(),
+     world.resource::<Assets<StandardMaterial>>().len() - world.resource::<SharedHandles>().cached_materials())
+}
+
+fn pooled(sim: &mut GameSim, kind: PoolKind) -> usize {
+    let world = sim.app.world_mut();
+    world.query::<&Pooled>().iter(world).filter(|pooled| pooled.kind == kind).count()
+}
+
+#[test]
+fn firing_and_exploding_add_no_assets() {
+    let mut sim = GameSim::default();
+    sim.press(KeyCode::Space);
+    sim.run(300);
+    let counts = asset_counts(&sim);
+
+    sim.run(300);
+    for _ in 0..10 {
+        sim.app.world_mut().send_event(CreateEffectEvent(Vec3::new(0.0, 0.0, -100.0), 1.0));
+        sim.run(1);
+    }
+    assert!(sim.app.world().resource::<GameStats>().shots > 80);
+    assert_eq!(asset_counts(&sim), counts);
+}
+
+#[test]
+fn the_valley_is_recycled_and_adds_no_assets() {
+    // the tower run is all valley, a segment needs ten seconds to fly past
+    let mut sim = GameSim::new(GameSimPlugin { level: 3, ..Default::default() });
+    sim.run(700);
+    let counts = asset_counts(&sim);
+    let world = sim.app.world_mut();
+    let meshes: Vec<AssetId<Mesh>> = world.query::<(&Pooled, &Handle<Mesh>)>().iter(world)
+        .filter(|(pooled, _)| pooled.kind == PoolKind::Segment)
+        .map(|(_, mesh)| mesh.id())
+        .collect();
+
+    sim.run(600);
+    let world = sim.app.world_mut();
+    let flying: Vec<AssetId<Mesh>> = world.query_filtered::<&Handle<Mesh>, With<Terrain>>().iter(world)
+        .map(|mesh| mesh.id())
+        .collect();
+    assert!(flying.len() > 10);
+    assert!(flying.iter().all(|mesh| meshes.contains(mesh)));
+    assert_eq!(asset_counts(&sim), counts);
+}
+
+#[test]
+fn lasers_are_recycled() {
+    let mut sim = GameSim::default();
+    sim.press(KeyCode::Space);
+    sim.run(600);
+
+    let shots = sim.app.world().resource::<GameStats>().shots as usize;
+    let lasers = pooled(&mut sim, PoolKind::Laser);
+    assert!(lasers < shots / 2, "{lasers} entities for {shots} shots");
+    assert!(sim.app.world().resource::<EntityPool>().free_count(PoolKind::Laser) > 0);
+}
+
+#[test]
+fn particles_are_recycled() {
+    let mut sim = GameSim::default();
+    for _ in 0..3 {
+        // 256 particles each
+        sim.app.world_mut().send_event(CreateEffectEvent(Vec3::new(0.0, 0.0, -100.0), 8.0));
+        sim.run(150);
+    }
+    let particles = pooled(&mut sim, PoolKind::Particle);
+    assert!(particles < 2 * 256, "{particles} particles");
+    assert!(sim.app.world().resource::<EntityPool>().free_count(PoolKind::Particle) >= 256);
+}
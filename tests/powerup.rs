@@ -0,0 +1,64 @@
+use bevy::ecs::system::RunSystemOnce;
+use bevy::prelude::*;
+use planet_rust::components::{LaserGun, Opponent, PowerUp, PowerUpKind, Ship};
+use planet_rust::pool::SharedHandles;
+use planet_rust::powerup::{spawn_power_up, POWER_UP_TIME};
+use planet_rust::resources::ActivePowerUps;
+use planet_rust::sim::GameSim;
+
+/// Drops a pickup a bit ahead of the ship and lets it fly in.
+fn collect(sim: &mut GameSim, kind: PowerUpKind) {
+    let translation = sim.ship_transform().translation + Vec3::new(0.0, 0.0, -20.0);
+    sim.app.world_mut().run_system_once(move |mut commands: Commands,
+                                              mut handles: ResMut<SharedHandles>,
+                                              mut materials: ResMut<Assets<StandardMaterial>>| {
+        spawn_power_up(&mut commands, &mut handles, &mut materials, translation, kind);
+    });
+    sim.run(60);
+}
+
+fn ship_guns(sim: &mut GameSim) -> usize {
+    let world = sim.app.world_mut();
+    world.query_filtered::<&LaserGun, With<Ship>>().single(world).positions.len()
+}
+
+#[test]
+fn a_shield_pickup_recharges_the_shields() {
+    let mut sim = GameSim::default();
+    sim.ship_health().current = 10.0;
+    collect(&mut sim, PowerUpKind::Shield);
+
+    assert_eq!(sim.count::<PowerUp>(), 0);
+    assert!(sim.ship_health().fraction() > 0.7);
+
+    // never more than full
+    sim.ship_health().current = 19.0;
+    collect(&mut sim, PowerUpKind::Shield);
+    assert_eq!(sim.ship_health().fraction(), 1.0);
+}
+
+#[test]
+fn spread_shot_adds_guns_until_it_runs_out() {
+    let mut sim = GameSim::default();
+    collect(&mut sim, PowerUpKind::SpreadShot);
+
+    assert!(sim.app.world().resource::<ActivePowerUps>().spread_shot > 0.0);
+    assert_eq!(ship_guns(&mut sim), 4);
+
+    sim.run((POWER_UP_TIME * 60.0) as usize);
+    assert_eq!(ship_guns(&mut sim), 2);
+}
+
+#[test]
+fn a_bomb_clears_the_screen() {
+    let mut sim = GameSim::default();
+    sim.run(130);
+    let opponents = sim.count::<Opponent>();
+    assert!(opponents > 0);
+    let hits = sim.ship().hits;
+
+    collect(&mut sim, PowerUpKind::Bomb);
+
+    // opponents keep coming, but everything that was there is gone and counts
+    assert!(sim.ship().hits <= hits - opponents as i32);
+}
@@ -0,0 +1,72 @@
+use bevy::prelude::*;
+use planet_rust::replay::{Replay, ReplayPlayer, ReplayPlugin, ReplayRecorder};
+use planet_rust::sim::{GameSim, GameSimPlugin};
+
+fn snapshot(sim: &mut GameSim) -> (usize, f32, i32, Vec3) {
+    let translation = sim.ship_transform().translation;
+    let shields = sim.ship_health().current;
+    let hits = sim.ship().hits;
+    (sim.level(), shields, hits, translation)
+}
+
+#[test]
+fn a_recorded_session_replays_exactly() {
+    let path = std::env::temp_dir().join("planet-rust-replays-exactly.replay");
+    let mut sim = GameSim::with_plugins(GameSimPlugin { seed: 3, ..default() },
+                                        ReplayPlugin::Record(path.clone()));
+    sim.press(KeyCode::Space);
+    for key in [KeyCode::ArrowLeft, KeyCode::ArrowUp, KeyCode::ArrowRight, KeyCode::ArrowDown] {
+        sim.press(key);
+        sim.run(150);
+        sim.release(key);
+    }
+    let recorded = snapshot(&mut sim);
+    let replay = sim.app.world().resource::<ReplayRecorder>().replay.clone();
+    assert_eq!(replay.frames.len(), 601);
+
+    assert_eq!(replay.seed, 3);
+    replay.save(&path).unwrap();
+    let loaded = Replay::load(&path).unwrap();
+    assert_eq!(loaded, replay);
+
+    let frames = loaded.frames.len();
+    let mut sim = GameSim::with_plugins(GameSimPlugin::default(), ReplayPlugin::Play(loaded));
+    sim.run(frames - 1);
+    assert!(sim.app.world().resource::<ReplayPlayer>().finished());
+    assert_eq!(snapshot(&mut sim), recorded);
+}
+
+#[test]
+fn a_replay_starts_at_its_recorded_level() {
+    let path = std::env::temp_dir().join("planet-rust-replays-level.replay");
+    let mut sim = GameSim::with_plugins(GameSimPlugin { seed: 5, level: 3, ..default() },
+                                        ReplayPlugin::Record(path.clone()));
+    sim.press(KeyCode::ArrowLeft);
+    sim.run(120);
+    let recorded = snapshot(&mut sim);
+    let replay = sim.app.world().resource::<ReplayRecorder>().replay.clone();
+    assert_eq!(replay.level, 3);
+    replay.save(&path).unwrap();
+
+    let loaded = Replay::load(&path).unwrap();
+    let frames = loaded.frames.len();
+    let mut sim = GameSim::with_plugins(GameSimPlugin::default(), ReplayPlugin::Play(loaded));
+    sim.run(frames - 1);
+    assert_eq!(snapshot(&mut sim), recorded);
+}
+
+#[test]
+fn replays_of_older_versions_start_at_the_first_level() {
+    let mut bytes = b"PRRP".to_vec();
+    bytes.extend(2u16.to_le_bytes());
+    bytes.extend(9u64.to_le_bytes());
+    bytes.extend(0u32.to_le_bytes());
+    let replay = Replay::read(&mut bytes.as_slice()).unwrap();
+    assert_eq!((replay.seed, replay.level, replay.frames.len()), (9, 1, 0));
+}
+
+#[test]
+fn other_files_are_rejected() {
+    let mut bytes: &[u8] = b"RIFF\x01\x00";
+    assert!(Replay::read(&mut bytes).is_err());
+}
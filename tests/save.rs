@@ -0,0 +1,143 @@
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use bevy::prelude::*;
+use planet_rust::components::{LaserGun, Planet};
+use planet_rust::game_state::GameState;
+use planet_rust::replay::{Replay, ReplayFrame, ReplayPlugin};
+use planet_rust::resources::{Score, UnlockedWeapons};
+use planet_rust::save::{Resume, SaveGame, SavePlugin, SaveSlots, SAVE_VERSION};
+use planet_rust::sim::{GameSim, GameSimPlugin};
+use planet_rust::weapon::Weapon;
+
+fn save_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join("planet-rust-save-test").join(name);
+    let _ = fs::remove_dir_all(&dir);
+    dir
+}
+
+#[test]
+fn the_game_is_saved_when_a_level_is_reached() {
+    let dir = save_dir("level");
+    let mut sim = GameSim::with_plugins(GameSimPlugin::default(), SavePlugin { dir: Some(dir.clone()) });
+    sim.app.world_mut().resource_mut::<Score>().points = 700;
+    sim.ship_health().current *= 0.5;
+    sim.finish_level();
+    assert_eq!(sim.level(), 2);
+    // written from the level change event, which may be read a frame later
+    sim.run(1);
+    let score = sim.app.world().resource::<Score>().points;
+    assert!(score > 700);
+
+    let save = SaveGame::load(&SaveGame::path(&dir, 0)).unwrap();
+    assert_eq!(save, SaveGame {
+        version: SAVE_VERSION,
+        level: 2,
+        shields: 0.5,
+        score,
+        weapons: vec![Weapon::Laser, Weapon::HomingMissile, Weapon::Scatter],
+    });
+    assert_eq!(sim.app.world().resource::<SaveSlots>().slots[0], Some(save));
+    assert_eq!(SaveSlots::load(Some(dir)).slots[0].as_ref().map(|save| save.level), Some(2));
+}
+
+#[test]
+fn continue_picks_up_where_the_save_left_off() {
+    let mut sim = GameSim::with_plugins(GameSimPlugin::default(), SavePlugin::default());
+    sim.app.world_mut().resource_mut::<Resume>().0 = Some(SaveGame {
+        version: SAVE_VERSION,
+        level: 3,
+        shields: 0.25,
+        score: 1200,
+        weapons: vec![Weapon::Laser, Weapon::ChargedBeam],
+    });
+    sim.set_state(GameState::Restart);
+    sim.run(1);
+    assert_eq!(sim.level(), 3);
+    assert_eq!(sim.ship().hits, 25);
+    assert_eq!(sim.ship_health().fraction(), 0.25);
+    assert_eq!(sim.app.world().resource::<Score>().points, 1200);
+    // the levels before it went by, planets included
+    assert_eq!(sim.count::<Planet>(), 0);
+
+    // the saved weapons and the ones of the levels before it
+    sim.run(1);
+    assert_eq!(sim.app.world().resource::<UnlockedWeapons>().0,
+               vec![Weapon::Laser, Weapon::ChargedBeam, Weapon::HomingMissile, Weapon::Scatter]);
+    sim.press(KeyCode::KeyW);
+    sim.run(1);
+    let world = sim.app.world_mut();
+    assert_eq!(world.query::<&LaserGun>().iter(world).find(|gun| gun.player).unwrap().weapon, Weapon::HomingMissile);
+
+    // the save is used once, a restart is a new game
+    sim.set_state(GameState::Restart);
+    sim.run(1);
+    assert_eq!(sim.level(), 1);
+}
+
+#[test]
+fn winning_the_campaign_empties_the_slot() {
+    let dir = save_dir("won");
+    let mut sim = GameSim::with_plugins(GameSimPlugin::default(), SavePlugin { dir: Some(dir.clone()) });
+    sim.finish_level();
+    sim.finish_level();
+    sim.run(1);
+    assert_eq!(sim.level(), 3);
+    assert!(SaveGame::path(&dir, 0).exists());
+
+    sim.finish_level();
+    sim.run(1);
+    assert_eq!(sim.state(), GameState::Won);
+    assert!(!SaveGame::path(&dir, 0).exists());
+    assert_eq!(sim.app.world().resource::<SaveSlots>().slots[0], None);
+}
+
+#[test]
+fn saves_of_another_version_are_refused() {
+    let dir = save_dir("version");
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(SaveGame::path(&dir, 0), "(version: 99, level: 2, checkpoint: \"valley\")").unwrap();
+    fs::write(SaveGame::path(&dir, 1), "not a save").unwrap();
+
+    let e = SaveGame::load(&SaveGame::path(&dir, 0)).unwrap_err();
+    assert_eq!(e.kind(), io::ErrorKind::InvalidData);
+    let slots = SaveSlots::load(Some(dir));
+    assert_eq!(slots.slots, vec![None, None, None]);
+}
+
+#[test]
+fn a_replay_leaves_the_saved_games_alone() {
+    let dir = save_dir("replay");
+    let path = SaveGame::path(&dir, 0);
+    let save = SaveGame {
+        version: SAVE_VERSION,
+        level: 2,
+        shields: 0.5,
+        score: 300,
+        weapons: vec![Weapon::Laser],
+    };
+    save.save(&path).unwrap();
+    let replay = Replay {
+        frames: vec![ReplayFrame { delta: 1.0 / 60.0, ..default() }; 300],
+        ..default()
+    };
+    let mut sim = GameSim::with_plugins(GameSimPlugin::default(),
+                                        (SavePlugin { dir: Some(dir.clone()) }, ReplayPlugin::Play(replay)));
+    for _ in 0..3 {
+        sim.finish_level();
+    }
+    sim.run(1);
+    assert_eq!(sim.state(), GameState::Won);
+    assert_eq!(SaveGame::load(&path).unwrap(), save);
+    assert_eq!(sim.app.world().resource::<SaveSlots>().slots[0], Some(save));
+}
+
+#[test]
+fn a_game_started_at_a_later_level_is_not_saved() {
+    let dir = save_dir("start-level");
+    let mut sim = GameSim::with_plugins(GameSimPlugin { level: 2, ..Default::default() }, SavePlugin { dir: Some(dir.clone()) });
+    sim.finish_level();
+    sim.run(1);
+    assert_eq!(sim.level(), 3);
+    assert!(!SaveGame::path(&dir, 0).exists());
+}
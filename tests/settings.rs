@@ -0,0 +1,55 @@
+use bevy::prelude::*;
+use bevy::window::{PresentMode, WindowMode};
+use planet_rust::settings::{Antialiasing, Resolution, Settings, SettingsPlugin};
+use planet_rust::sim::{GameSim, GameSimPlugin};
+
+#[test]
+fn settings_survive_a_restart() {
+    let path = std::env::temp_dir().join("planet-rust-settings-test").join("settings.ron");
+    let settings = Settings {
+        resolution: Resolution { width: 1600, height: 900 },
+        fullscreen: true,
+        vsync: false,
+        antialiasing: Antialiasing::Off,
+        shadows: false,
+    };
+    settings.save(&path).unwrap();
+    assert_eq!(Settings::load(&path).unwrap(), settings);
+
+    // a file from an older version misses the newer options
+    std::fs::write(&path, "(resolution: (width: 1280, height: 720))").unwrap();
+    assert_eq!(Settings::load(&path).unwrap(), Settings {
+        resolution: Resolution { width: 1280, height: 720 },
+        ..Default::default()
+    });
+}
+
+#[test]
+fn the_window_is_created_from_the_settings() {
+    let window = Settings::default().window();
+    assert_eq!(window.mode, WindowMode::Windowed);
+    assert_eq!(window.present_mode, PresentMode::AutoVsync);
+    assert_eq!((window.resolution.width(), window.resolution.height()), (920.0, 640.0));
+
+    let window = Settings { fullscreen: true, vsync: false, ..Default::default() }.window();
+    assert_eq!(window.mode, WindowMode::BorderlessFullscreen);
+    assert_eq!(window.present_mode, PresentMode::AutoNoVsync);
+}
+
+#[test]
+fn graphics_settings_apply_while_playing() {
+    let mut sim = GameSim::with_plugins(GameSimPlugin::default(), SettingsPlugin);
+    assert_eq!(*sim.app.world().resource::<Msaa>(), Msaa::Sample4);
+
+    *sim.app.world_mut().resource_mut::<Settings>() = Settings {
+        antialiasing: Antialiasing::X2,
+        shadows: false,
+        ..Default::default()
+    };
+    sim.run(1);
+    assert_eq!(*sim.app.world().resource::<Msaa>(), Msaa::Sample2);
+    let world = sim.app.world_mut();
+    let lights: Vec<bool> = world.query::<&DirectionalLight>().iter(world).map(|light| light.shadows_enabled).collect();
+    assert!(!lights.is_empty());
+    assert!(lights.iter().all(|shadows| !shadows));
+}
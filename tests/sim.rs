@@ -0,0 +1,246 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use planet_rust::components::{Boss, Health, Laser, Opponent, WeakPoint, WinOrLostState};
+use planet_rust::events::{DamageEvent, DamageKind};
+use planet_rust::game_state::GameState;
+use planet_rust::resources::Score;
+use planet_rust::sim::{GameSim, GameSimPlugin};
+
+#[test]
+fn ship_stays_inside_the_play_area() {
+    let mut sim = GameSim::default();
+    sim.press(KeyCode::ArrowLeft);
+    sim.press(KeyCode::ArrowUp);
+    sim.run(120);
+
+    let transform = sim.ship_transform();
+    assert_eq!(transform.translation.x, -15.0);
+    assert_eq!(transform.translation.y, 10.0);
+}
+
+#[test]
+fn space_fires_the_player_lasers() {
+    let mut sim = GameSim::default();
+    assert_eq!(sim.count::<Laser>(), 0);
+
+    sim.press(KeyCode::Space);
+    sim.run(30);
+    assert!(sim.count::<Laser>() >= 2);
+}
+
+#[test]
+fn opponents_spawn_from_the_level_definition() {
+    let mut sim = GameSim::default();
+    sim.run(60);
+    assert_eq!(sim.count::<Opponent>(), 0);
+
+    // a formation spawns several fighters at once
+    sim.run(70);
+    assert!(sim.count::<Opponent>() >= 1);
+}
+
+#[test]
+fn enough_hits_advance_the_level() {
+    let mut sim = GameSim::default();
+    assert_eq!(sim.level(), 1);
+
+    sim.ship().hits = 0;
+    sim.run(1);
+    assert_eq!(sim.level(), 1);
+    assert_eq!(sim.count::<Boss>(), 1);
+
+    sim.defeat_boss();
+    sim.run(1);
+    assert_eq!(sim.count::<Boss>(), 0);
+    assert_eq!(sim.level(), 2);
+    assert_eq!(sim.ship().hits, 40);
+}
+
+#[test]
+fn empty_shields_end_the_game() {
+    let mut sim = GameSim::default();
+    sim.ship_health().current = 0.0;
+    sim.run(2);
+
+    assert_eq!(sim.state(), GameState::End);
+    assert!(sim.ship().win_or_lost == WinOrLostState::Lost);
+}
+
+#[test]
+fn the_same_seed_spawns_the_same_opponents() {
+    fn run_with_seed(seed: u64) -> Vec<Vec3> {
+        let mut sim = GameSim::new(GameSimPlugin { seed, ..default() });
+        sim.run(250);
+        opponent_positions(&mut sim)
+    }
+
+    let positions = run_with_seed(42);
+    assert_eq!(positions.len(), 2);
+    assert_eq!(positions, run_with_seed(42));
+    assert_ne!(positions, run_with_seed(7));
+}
+
+#[test]
+fn finishing_the_last_level_wins_the_game() {
+    let mut sim = GameSim::default();
+    for level in 2..=3 {
+        sim.finish_level();
+        assert_eq!(sim.level(), level);
+    }
+    assert_eq!(sim.state(), GameState::Running);
+
+    // the last level ends with its boss too
+    sim.ship().hits = 0;
+    sim.run(1);
+    assert_eq!(sim.count::<Boss>(), 1);
+    assert_eq!(sim.state(), GameState::Running);
+
+    sim.defeat_boss();
+    sim.run(2);
+    assert_eq!(sim.level(), 3);
+    assert_eq!(sim.state(), GameState::Won);
+    assert!(sim.ship().win_or_lost == WinOrLostState::Win);
+}
+
+#[test]
+fn pausing_freezes_the_game() {
+    let mut sim = GameSim::default();
+    sim.run(200);
+    let opponent = opponent_positions(&mut sim);
+    assert!(!opponent.is_empty());
+
+    sim.set_state(GameState::Paused);
+    let ship = sim.ship_transform();
+    sim.press(KeyCode::ArrowLeft);
+    sim.press(KeyCode::Space);
+    sim.run(60);
+    assert_eq!(sim.ship_transform(), ship);
+    assert_eq!(opponent_positions(&mut sim), opponent);
+    assert_eq!(sim.count::<Laser>(), 0);
+
+    sim.set_state(GameState::Running);
+    sim.run(10);
+    assert!(sim.ship_transform().translation.x < ship.translation.x);
+    assert_ne!(opponent_positions(&mut sim), opponent);
+}
+
+#[test]
+fn restart_starts_a_fresh_game() {
+    let mut sim = GameSim::default();
+    sim.press(KeyCode::Space);
+    sim.run(200);
+    sim.finish_level();
+    sim.ship_health().current = 0.0;
+    sim.run(2);
+    assert_eq!(sim.state(), GameState::End);
+    assert_eq!(sim.level(), 2);
+
+    sim.release(KeyCode::Space);
+    sim.set_state(GameState::Restart);
+    sim.run(1);
+    assert_eq!(sim.state(), GameState::Running);
+    assert_eq!(sim.level(), 1);
+    assert_eq!(sim.ship_health().fraction(), 1.0);
+    assert_eq!(sim.ship().hits, 40);
+    assert!(sim.ship().win_or_lost == WinOrLostState::Neutral);
+    assert_eq!(sim.count::<Opponent>(), 0);
+    assert_eq!(sim.count::<Laser>(), 0);
+}
+
+fn opponent_positions(sim: &mut GameSim) -> Vec<Vec3> {
+    let world = sim.app.world_mut();
+    world.query_filtered::<&Transform, With<Opponent>>()
+        .iter(world)
+        .map(|transform| transform.translation)
+        .collect()
+}
+
+#[test]
+fn shooting_an_opponent_scores_its_points() {
+    let mut sim = GameSim::default();
+    let opponent = sim.spawn_opponent(-Vec3::Z * 60.0, 1.0);
+    sim.app.world_mut().entity_mut(opponent).insert(Collider::cuboid(10.0, 10.0, 3.0));
+    sim.app.world_mut().resource_mut::<Score>().streak = 5;
+    sim.press(KeyCode::Space);
+    sim.run(30);
+
+    assert_eq!(sim.count::<Opponent>(), 0);
+    let score = sim.app.world().resource::<Score>();
+    assert_eq!(score.points, 200);
+    assert_eq!(score.streak, 6);
+}
+
+#[test]
+fn the_boss_core_is_shielded_by_its_weak_points() {
+    let mut sim = GameSim::default();
+    sim.ship().hits = 0;
+    sim.run(1);
+    let weak_points: f32 = weak_point_health(&mut sim).iter().sum();
+
+    // the boss flies in and holds in front of the ship
+    sim.press(KeyCode::Space);
+    sim.run(600);
+    assert!(sim.count::<WeakPoint>() > 0);
+    assert!(weak_point_health(&mut sim).iter().sum::<f32>() < weak_points);
+    assert_eq!(boss_health(&mut sim).fraction(), 1.0);
+    assert_eq!(boss(&mut sim).phase, 0);
+
+    // below a quarter of its health it is in the last phase
+    let max = boss_health(&mut sim).max;
+    boss_health(&mut sim).current = max / 5.0;
+    sim.run(1);
+    assert_eq!(boss(&mut sim).phase, 2);
+    assert_eq!(sim.level(), 1);
+}
+
+#[test]
+fn every_bit_of_damage_wears_the_boss_down() {
+    let mut sim = GameSim::default();
+    sim.ship().hits = 0;
+    sim.run(1);
+    let world = sim.app.world_mut();
+    let weak_points: Vec<Entity> = world.query_filtered::<Entity, With<WeakPoint>>().iter(world).collect();
+    for target in &weak_points {
+        world.send_event(DamageEvent {
+            target: *target,
+            amount: 100.0,
+            kind: DamageKind::Projectile,
+        });
+    }
+    sim.run(1);
+    assert_eq!(sim.count::<WeakPoint>(), 0);
+
+    let world = sim.app.world_mut();
+    let core = world.query_filtered::<Entity, With<Boss>>().single(world);
+    world.send_event(DamageEvent {
+        target: core,
+        amount: 0.25,
+        kind: DamageKind::Projectile,
+    });
+    sim.run(1);
+    let health = boss_health(&mut sim);
+    assert_eq!(health.current, health.max - 0.25);
+
+    let score = sim.app.world().resource::<Score>().points;
+    sim.defeat_boss();
+    sim.run(1);
+    assert_eq!(sim.count::<Boss>(), 0);
+    assert!(sim.app.world().resource::<Score>().points > score);
+}
+
+fn boss(sim: &mut GameSim) -> Mut<'_, Boss> {
+    let world = sim.app.world_mut();
+    let entity = world.query_filtered::<Entity, With<Boss>>().single(world);
+    world.get_mut::<Boss>(entity).unwrap()
+}
+
+fn boss_health(sim: &mut GameSim) -> Mut<'_, Health> {
+    let world = sim.app.world_mut();
+    let entity = world.query_filtered::<Entity, With<Boss>>().single(world);
+    world.get_mut::<Health>(entity).unwrap()
+}
+
+fn weak_point_health(sim: &mut GameSim) -> Vec<f32> {
+    let world = sim.app.world_mut();
+    world.query_filtered::<&Health, With<WeakPoint>>().iter(world).map(|health| health.current).collect()
+}
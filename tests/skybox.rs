@@ -0,0 +1,30 @@
+use bevy::prelude::*;
+use planet_rust::game_state::GameState;
+use planet_rust::sim::{GameSim, GameSimPlugin};
+use planet_rust::skybox::SkyboxPlugin;
+
+fn skybox_rotation(sim: &mut GameSim) -> Quat {
+    let world = sim.app.world_mut();
+    world.query::<(&Name, &Transform)>().iter(world)
+        .find(|(name, _)| name.as_str() == "Skybox")
+        .map(|(_, transform)| transform.rotation)
+        .unwrap()
+}
+
+#[test]
+fn the_skybox_only_turns_while_playing_and_a_new_game_starts_it_over() {
+    let mut sim = GameSim::with_plugins(GameSimPlugin::default(), SkyboxPlugin);
+    sim.finish_level();
+    sim.run(30);
+    let turned = skybox_rotation(&mut sim);
+    assert_ne!(turned, Quat::IDENTITY);
+
+    sim.set_state(GameState::Paused);
+    sim.run(30);
+    assert_eq!(skybox_rotation(&mut sim), turned);
+
+    sim.set_state(GameState::Running);
+    sim.set_state(GameState::Restart);
+    sim.run(60);
+    assert_eq!(skybox_rotation(&mut sim), Quat::IDENTITY);
+}
@@ -0,0 +1,106 @@
+use bevy::prelude::*;
+use planet_rust::components::{Damage, Laser, LaserGun, Opponent, Ship};
+use planet_rust::game_state::GameState;
+use planet_rust::resources::{StartLevel, UnlockedWeapons};
+use planet_rust::sim::{GameSim, GameSimPlugin};
+use planet_rust::weapon::Weapon;
+
+fn ship_gun(sim: &mut GameSim) -> Mut<'_, LaserGun> {
+    let world = sim.app.world_mut();
+    let entity = world.query_filtered::<Entity, With<Ship>>().single(world);
+    world.get_mut::<LaserGun>(entity).unwrap()
+}
+
+fn player_shots(sim: &mut GameSim) -> Vec<(Transform, f32)> {
+    let world = sim.app.world_mut();
+    world.query::<(&Transform, &Laser, &Damage)>().iter(world)
+        .filter(|(_, laser, _)| laser.player)
+        .map(|(transform, _, damage)| (*transform, damage.amount))
+        .collect()
+}
+
+fn next_weapon(sim: &mut GameSim) -> Weapon {
+    sim.press(KeyCode::KeyW);
+    sim.run(2);
+    sim.release(KeyCode::KeyW);
+    sim.run(2);
+    ship_gun(sim).weapon
+}
+
+#[test]
+fn the_next_weapon_key_cycles_through_the_unlocked_weapons() {
+    let mut sim = GameSim::default();
+    sim.app.world_mut().insert_resource(UnlockedWeapons(Weapon::ALL.to_vec()));
+    assert_eq!(ship_gun(&mut sim).weapon, Weapon::Laser);
+    for weapon in [Weapon::HomingMissile, Weapon::ChargedBeam, Weapon::Scatter, Weapon::Laser] {
+        assert_eq!(next_weapon(&mut sim), weapon);
+    }
+}
+
+#[test]
+fn a_new_game_has_the_laser_and_the_levels_unlock_the_rest() {
+    let mut sim = GameSim::default();
+    assert_eq!(next_weapon(&mut sim), Weapon::Laser);
+
+    sim.finish_level();
+    sim.run(1);
+    assert_eq!(sim.app.world().resource::<UnlockedWeapons>().0,
+               vec![Weapon::Laser, Weapon::HomingMissile, Weapon::Scatter]);
+    assert_eq!(next_weapon(&mut sim), Weapon::HomingMissile);
+    assert_eq!(next_weapon(&mut sim), Weapon::Scatter);
+    assert_eq!(next_weapon(&mut sim), Weapon::Laser);
+
+    // a game started at a later level has the weapons of the levels before it
+    let mut sim = GameSim::new(GameSimPlugin { level: 3, ..Default::default() });
+    sim.run(1);
+    assert_eq!(sim.app.world().resource::<UnlockedWeapons>().0.len(), Weapon::ALL.len());
+
+    // a new game starts over
+    sim.app.world_mut().resource_mut::<StartLevel>().0 = 1;
+    sim.set_state(GameState::Restart);
+    sim.run(1);
+    assert_eq!(sim.app.world().resource::<UnlockedWeapons>().0, vec![Weapon::Laser]);
+}
+
+#[test]
+fn the_charged_beam_fires_on_release() {
+    let mut sim = GameSim::default();
+    ship_gun(&mut sim).weapon = Weapon::ChargedBeam;
+    sim.press(KeyCode::Space);
+    sim.run(120);
+    assert!(player_shots(&mut sim).is_empty());
+
+    sim.release(KeyCode::Space);
+    sim.run(2);
+    let shots = player_shots(&mut sim);
+    assert_eq!(shots.len(), 2);
+    assert!(shots.iter().all(|(_, damage)| *damage == 5.0));
+}
+
+#[test]
+fn scatter_fires_a_fan_from_every_gun() {
+    let mut sim = GameSim::default();
+    ship_gun(&mut sim).weapon = Weapon::Scatter;
+    sim.press(KeyCode::Space);
+    sim.run(2);
+
+    let shots = player_shots(&mut sim);
+    assert_eq!(shots.len(), 10);
+    assert!(shots.iter().any(|(transform, _)| transform.forward().x > 0.1));
+    assert!(shots.iter().any(|(transform, _)| transform.forward().x < -0.1));
+}
+
+#[test]
+fn a_missile_turns_towards_its_target_and_hits_harder() {
+    let mut sim = GameSim::default();
+    ship_gun(&mut sim).weapon = Weapon::HomingMissile;
+    sim.spawn_opponent(Vec3::new(12.0, 0.0, -60.0), 2.0);
+    sim.press(KeyCode::Space);
+    sim.run(2);
+    sim.release(KeyCode::Space);
+    sim.run(10);
+    assert!(player_shots(&mut sim).iter().all(|(transform, _)| transform.forward().x > 0.0));
+
+    sim.run(40);
+    assert_eq!(sim.count::<Opponent>(), 0);
+}